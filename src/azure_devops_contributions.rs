@@ -0,0 +1,242 @@
+use crate::{
+    errors::ProviderError,
+    http::{HttpClient, Quota},
+    processor::Event,
+    provider::{BoxFuture, Events, GenericConfig, Provider, ProviderConfig, into_events},
+};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+const API_VERSION: &str = "7.0";
+const PAGE_SIZE: usize = 100;
+
+#[derive(Debug, Deserialize)]
+struct AzureDevOpsList<T> {
+    value: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AzureDevOpsConnectionData {
+    authenticated_user: AzureDevOpsIdentity,
+}
+
+#[derive(Debug, Deserialize)]
+struct AzureDevOpsIdentity {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AzureDevOpsProject {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AzureDevOpsRepository {
+    id: String,
+    #[serde(default)]
+    is_disabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct AzureDevOpsGitUserDate {
+    date: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AzureDevOpsCommit {
+    author: AzureDevOpsGitUserDate,
+}
+
+impl Event for AzureDevOpsCommit {
+    fn timestamp(&self) -> Result<OffsetDateTime, ProviderError> {
+        OffsetDateTime::parse(&self.author.date, &Rfc3339)
+            .map_err(|e| ProviderError::DateError(format!("Failed to parse date: {}", e)))
+    }
+    fn contributions(&self) -> i64 {
+        1
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AzureDevOpsPullRequest {
+    creation_date: String,
+}
+
+impl Event for AzureDevOpsPullRequest {
+    fn timestamp(&self) -> Result<OffsetDateTime, ProviderError> {
+        OffsetDateTime::parse(&self.creation_date, &Rfc3339)
+            .map_err(|e| ProviderError::DateError(format!("Failed to parse date: {}", e)))
+    }
+    fn contributions(&self) -> i64 {
+        1
+    }
+}
+
+pub struct AzureDevOpsClient {
+    client: HttpClient,
+    config: GenericConfig,
+}
+
+impl AzureDevOpsClient {
+    fn new(config: GenericConfig) -> Self {
+        Self {
+            client: HttpClient::new("Azure DevOps"),
+            config,
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<(T, Option<String>), ProviderError> {
+        let endpoint = format!("{}/{}", self.config.server(), path);
+
+        let request = self
+            .client
+            .get(endpoint)
+            .query(&[("api-version", API_VERSION)])
+            .query(query)
+            .basic_auth("", Some(self.config.token()))
+            .header("accept", "application/json");
+        let response = self.client.send(request).await?;
+        let response = self.client.error_for_status(response).await?;
+
+        let continuation_token = response
+            .headers()
+            .get("x-ms-continuationtoken")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        Ok((self.client.json(response).await?, continuation_token))
+    }
+
+    /// Fetches every page of a `$top`/`$skip` paginated list.
+    async fn get_all<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+        top_parameter: &str,
+        skip_parameter: &str,
+    ) -> Result<Vec<T>, ProviderError> {
+        let mut values = Vec::new();
+        loop {
+            let mut page_query = query.to_vec();
+            page_query.push((top_parameter, PAGE_SIZE.to_string()));
+            page_query.push((skip_parameter, values.len().to_string()));
+
+            let (page, _): (AzureDevOpsList<T>, _) = self.get(path, &page_query).await?;
+            let page_size = page.value.len();
+            values.extend(page.value);
+            if page_size < PAGE_SIZE {
+                break;
+            }
+        }
+        Ok(values)
+    }
+
+    async fn fetch_user_id(&self) -> Result<String, ProviderError> {
+        let (connection_data, _): (AzureDevOpsConnectionData, _) =
+            self.get("_apis/connectionData", &[]).await?;
+        Ok(connection_data.authenticated_user.id)
+    }
+
+    async fn fetch_projects(&self) -> Result<Vec<AzureDevOpsProject>, ProviderError> {
+        let mut projects = Vec::new();
+        let mut continuation_token = None;
+        loop {
+            let mut query = vec![("$top", PAGE_SIZE.to_string())];
+            if let Some(token) = continuation_token {
+                query.push(("continuationToken", token));
+            }
+
+            let (page, next_token): (AzureDevOpsList<AzureDevOpsProject>, _) =
+                self.get("_apis/projects", &query).await?;
+            projects.extend(page.value);
+            match next_token {
+                Some(token) => continuation_token = Some(token),
+                None => break,
+            }
+        }
+        Ok(projects)
+    }
+}
+
+impl Provider for AzureDevOpsClient {
+    fn name(&self) -> &str {
+        "Azure DevOps"
+    }
+
+    async fn from_env(prefix: &str) -> Result<Self, ProviderError> {
+        Ok(Self::new(GenericConfig::from_env(prefix).await?))
+    }
+
+    fn fetch_events(
+        &self,
+        start_date: OffsetDateTime,
+        end_date: OffsetDateTime,
+    ) -> BoxFuture<'_, Result<Events, ProviderError>> {
+        Box::pin(async move {
+            let format_date = |date: OffsetDateTime| {
+                date.format(&Rfc3339)
+                    .map_err(|e| ProviderError::DateError(format!("Failed to format date: {}", e)))
+            };
+            let user_id = self.fetch_user_id().await?;
+
+            let mut commits = Vec::new();
+            let mut pull_requests = Vec::new();
+            for project in self.fetch_projects().await? {
+                let (repositories, _): (AzureDevOpsList<AzureDevOpsRepository>, _) = self
+                    .get(&format!("{}/_apis/git/repositories", project.name), &[])
+                    .await?;
+
+                for repository in repositories.value {
+                    if repository.is_disabled {
+                        continue;
+                    }
+                    commits.extend(
+                        self.get_all::<AzureDevOpsCommit>(
+                            &format!(
+                                "{}/_apis/git/repositories/{}/commits",
+                                project.name, repository.id
+                            ),
+                            &[
+                                ("searchCriteria.author", self.config.username().to_string()),
+                                ("searchCriteria.fromDate", format_date(start_date)?),
+                                ("searchCriteria.toDate", format_date(end_date)?),
+                            ],
+                            "searchCriteria.$top",
+                            "searchCriteria.$skip",
+                        )
+                        .await?,
+                    );
+                }
+
+                pull_requests.extend(
+                    self.get_all::<AzureDevOpsPullRequest>(
+                        &format!("{}/_apis/git/pullrequests", project.name),
+                        &[
+                            ("searchCriteria.creatorId", user_id.clone()),
+                            ("searchCriteria.status", "all".to_string()),
+                        ],
+                        "$top",
+                        "$skip",
+                    )
+                    .await?,
+                );
+            }
+
+            let mut events = into_events(commits);
+            events.extend(into_events(pull_requests));
+            Ok(events)
+        })
+    }
+
+    fn quota(&self) -> Option<Quota> {
+        self.client.quota()
+    }
+}
@@ -0,0 +1,327 @@
+use crate::{
+    ProviderError,
+    http::{HttpClient, Quota},
+    processor::Event,
+    provider::{BoxFuture, Events, Provider, into_events, required_var, secret_var, var},
+    secret::Secret,
+};
+use reqwest::RequestBuilder;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+const BITBUCKET_API: &str = "https://api.bitbucket.org/2.0";
+
+#[derive(Debug)]
+enum BitbucketAuth {
+    /// App password, sent as HTTP basic auth together with the username.
+    AppPassword(Secret),
+    /// Workspace, project or repository access token, sent as a bearer token.
+    AccessToken(Secret),
+}
+
+#[derive(Debug)]
+struct BitbucketConfig {
+    username: String,
+    auth: BitbucketAuth,
+    workspace: Option<String>,
+    email: Option<String>,
+}
+
+impl BitbucketConfig {
+    async fn from_env(prefix: &str) -> Result<Self, ProviderError> {
+        let auth = match (
+            secret_var(prefix, "APP_PASSWORD").await?,
+            secret_var(prefix, "TOKEN").await?,
+        ) {
+            (Some(password), _) => BitbucketAuth::AppPassword(password),
+            (None, Some(token)) => BitbucketAuth::AccessToken(token),
+            (None, None) => {
+                return Err(ProviderError::ConfigError(format!(
+                    "{0}_APP_PASSWORD or {0}_TOKEN (or one of their _FILE, _COMMAND, \
+                     _GIT_CREDENTIAL and _KEYRING variants) must be set",
+                    prefix
+                )));
+            }
+        };
+
+        Ok(Self {
+            username: required_var(prefix, "USERNAME")?,
+            auth,
+            workspace: var(prefix, "WORKSPACE"),
+            email: var(prefix, "EMAIL"),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketPage<T> {
+    values: Vec<T>,
+    next: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketRepository {
+    full_name: String,
+    updated_on: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketUser {
+    nickname: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketAuthor {
+    raw: String,
+    user: Option<BitbucketUser>,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Deserialize)]
+struct BitbucketCommit {
+    hash: String,
+    date: String,
+    author: BitbucketAuthor,
+}
+
+impl BitbucketCommit {
+    fn is_authored_by(&self, config: &BitbucketConfig) -> bool {
+        let nickname_matches = self
+            .author
+            .user
+            .as_ref()
+            .and_then(|user| user.nickname.as_deref())
+            .is_some_and(|nickname| nickname.eq_ignore_ascii_case(&config.username));
+        let email_matches = config.email.as_ref().is_some_and(|email| {
+            self.author
+                .raw
+                .to_lowercase()
+                .contains(&format!("<{}>", email.to_lowercase()))
+        });
+        nickname_matches || email_matches
+    }
+}
+
+impl Event for BitbucketCommit {
+    fn timestamp(&self) -> Result<OffsetDateTime, ProviderError> {
+        OffsetDateTime::parse(&self.date, &Rfc3339)
+            .map_err(|e| ProviderError::DateError(format!("Failed to parse date: {}", e)))
+    }
+    fn contributions(&self) -> i64 {
+        1
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Deserialize)]
+struct BitbucketPullRequest {
+    id: i64,
+    created_on: String,
+}
+
+impl Event for BitbucketPullRequest {
+    fn timestamp(&self) -> Result<OffsetDateTime, ProviderError> {
+        OffsetDateTime::parse(&self.created_on, &Rfc3339)
+            .map_err(|e| ProviderError::DateError(format!("Failed to parse date: {}", e)))
+    }
+    fn contributions(&self) -> i64 {
+        1
+    }
+}
+
+pub struct BitbucketClient {
+    client: HttpClient,
+    config: BitbucketConfig,
+}
+
+impl BitbucketClient {
+    fn new(config: BitbucketConfig) -> Self {
+        Self {
+            client: HttpClient::new("Bitbucket"),
+            config,
+        }
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        match &self.config.auth {
+            BitbucketAuth::AppPassword(password) => {
+                request.basic_auth(&self.config.username, Some(password.expose()))
+            }
+            BitbucketAuth::AccessToken(token) => request.bearer_auth(token.expose()),
+        }
+    }
+
+    async fn fetch_page<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        query: &[(&str, String)],
+    ) -> Result<BitbucketPage<T>, ProviderError> {
+        let request = self
+            .authorize(self.client.get(url).query(query))
+            .header("accept", "application/json");
+        let response = self.client.send(request).await?;
+        let response = self.client.error_for_status(response).await?;
+
+        self.client.json(response).await
+    }
+
+    async fn fetch_repositories(
+        &self,
+        start_date: OffsetDateTime,
+    ) -> Result<Vec<BitbucketRepository>, ProviderError> {
+        let (mut url, mut query) = match &self.config.workspace {
+            Some(workspace) => (
+                format!("{}/repositories/{}", BITBUCKET_API, workspace),
+                vec![("pagelen", "100".to_string())],
+            ),
+            None => (
+                format!("{}/repositories", BITBUCKET_API),
+                vec![
+                    ("role", "member".to_string()),
+                    ("pagelen", "100".to_string()),
+                ],
+            ),
+        };
+
+        let mut repositories = Vec::new();
+        loop {
+            let page: BitbucketPage<BitbucketRepository> = self.fetch_page(&url, &query).await?;
+            repositories.extend(page.values);
+            match page.next {
+                Some(next) => {
+                    url = next;
+                    query.clear();
+                }
+                None => break,
+            }
+        }
+
+        // Repositories that have not been touched since the start of the window
+        // cannot contain any commits within it.
+        repositories.retain(|repository| {
+            repository
+                .updated_on
+                .as_deref()
+                .and_then(|updated_on| OffsetDateTime::parse(updated_on, &Rfc3339).ok())
+                .is_none_or(|updated_on| updated_on >= start_date)
+        });
+        Ok(repositories)
+    }
+
+    async fn fetch_commits(
+        &self,
+        repository: &BitbucketRepository,
+        start_date: OffsetDateTime,
+        end_date: OffsetDateTime,
+    ) -> Result<Vec<BitbucketCommit>, ProviderError> {
+        let mut url = format!(
+            "{}/repositories/{}/commits",
+            BITBUCKET_API, repository.full_name
+        );
+        let mut query = vec![("pagelen", "100".to_string())];
+
+        let mut commits = Vec::new();
+        loop {
+            let page: BitbucketPage<BitbucketCommit> = self.fetch_page(&url, &query).await?;
+
+            // Commits are returned newest first, so paging can stop as soon as a
+            // page reaches past the start of the window.
+            let mut reached_start = false;
+            for commit in page.values {
+                let date = commit.timestamp()?;
+                if date < start_date {
+                    reached_start = true;
+                    continue;
+                }
+                if date < end_date && commit.is_authored_by(&self.config) {
+                    commits.push(commit);
+                }
+            }
+
+            match page.next {
+                Some(next) if !reached_start => {
+                    url = next;
+                    query.clear();
+                }
+                _ => break,
+            }
+        }
+        Ok(commits)
+    }
+
+    async fn fetch_pull_requests(
+        &self,
+        repository: &BitbucketRepository,
+        start_date: OffsetDateTime,
+    ) -> Result<Vec<BitbucketPullRequest>, ProviderError> {
+        let mut url = format!(
+            "{}/repositories/{}/pullrequests",
+            BITBUCKET_API, repository.full_name
+        );
+        let mut query = vec![
+            (
+                "q",
+                format!(
+                    "author.nickname=\"{}\" AND created_on>={}",
+                    self.config.username,
+                    start_date.date()
+                ),
+            ),
+            ("state", "OPEN".to_string()),
+            ("state", "MERGED".to_string()),
+            ("state", "DECLINED".to_string()),
+            ("state", "SUPERSEDED".to_string()),
+            ("pagelen", "50".to_string()),
+        ];
+
+        let mut pull_requests = Vec::new();
+        loop {
+            let page: BitbucketPage<BitbucketPullRequest> = self.fetch_page(&url, &query).await?;
+            pull_requests.extend(page.values);
+            match page.next {
+                Some(next) => {
+                    url = next;
+                    query.clear();
+                }
+                None => break,
+            }
+        }
+        Ok(pull_requests)
+    }
+}
+
+impl Provider for BitbucketClient {
+    fn name(&self) -> &str {
+        "Bitbucket"
+    }
+
+    async fn from_env(prefix: &str) -> Result<Self, ProviderError> {
+        Ok(Self::new(BitbucketConfig::from_env(prefix).await?))
+    }
+
+    fn fetch_events(
+        &self,
+        start_date: OffsetDateTime,
+        end_date: OffsetDateTime,
+    ) -> BoxFuture<'_, Result<Events, ProviderError>> {
+        Box::pin(async move {
+            let mut events = Vec::new();
+            for repository in self.fetch_repositories(start_date).await? {
+                events.extend(into_events(
+                    self.fetch_commits(&repository, start_date, end_date)
+                        .await?,
+                ));
+                events.extend(into_events(
+                    self.fetch_pull_requests(&repository, start_date).await?,
+                ));
+            }
+            Ok(events)
+        })
+    }
+
+    fn quota(&self) -> Option<Quota> {
+        self.client.quota()
+    }
+}
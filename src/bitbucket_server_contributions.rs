@@ -0,0 +1,271 @@
+use crate::{
+    errors::ProviderError,
+    http::{HttpClient, Quota},
+    processor::Event,
+    provider::{BoxFuture, Events, GenericConfig, Provider, ProviderConfig, into_events, var},
+};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use time::OffsetDateTime;
+
+#[derive(Debug)]
+struct BitbucketServerConfig {
+    generic: GenericConfig,
+    email: Option<String>,
+}
+
+impl BitbucketServerConfig {
+    async fn from_env(prefix: &str) -> Result<Self, ProviderError> {
+        Ok(Self {
+            generic: GenericConfig::from_env(prefix).await?,
+            email: var(prefix, "EMAIL"),
+        })
+    }
+}
+
+impl ProviderConfig for BitbucketServerConfig {
+    fn server(&self) -> &str {
+        self.generic.server()
+    }
+    fn username(&self) -> &str {
+        self.generic.username()
+    }
+    fn token(&self) -> &str {
+        self.generic.token()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BitbucketServerPage<T> {
+    values: Vec<T>,
+    is_last_page: bool,
+    next_page_start: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketServerProject {
+    key: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketServerRepository {
+    slug: String,
+    project: BitbucketServerProject,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BitbucketServerPerson {
+    name: String,
+    email_address: Option<String>,
+    slug: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BitbucketServerCommit {
+    author: BitbucketServerPerson,
+    author_timestamp: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketServerReviewer {
+    user: BitbucketServerPerson,
+    status: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BitbucketServerPullRequest {
+    created_date: i64,
+    updated_date: i64,
+    #[serde(default)]
+    reviewers: Vec<BitbucketServerReviewer>,
+}
+
+/// A single commit or pull request action, timestamped in milliseconds since the epoch.
+#[derive(Debug)]
+struct BitbucketServerActivity {
+    timestamp: i64,
+}
+
+impl Event for BitbucketServerActivity {
+    fn timestamp(&self) -> Result<OffsetDateTime, ProviderError> {
+        OffsetDateTime::from_unix_timestamp_nanos(self.timestamp as i128 * 1_000_000)
+            .map_err(|e| ProviderError::DateError(format!("Failed to parse date: {}", e)))
+    }
+    fn contributions(&self) -> i64 {
+        1
+    }
+}
+
+pub struct BitbucketServerClient {
+    client: HttpClient,
+    config: BitbucketServerConfig,
+}
+
+impl BitbucketServerClient {
+    fn new(config: BitbucketServerConfig) -> Self {
+        Self {
+            client: HttpClient::new("Bitbucket Server"),
+            config,
+        }
+    }
+
+    fn is_user(&self, person: &BitbucketServerPerson) -> bool {
+        let username = self.config.username();
+        person.name.eq_ignore_ascii_case(username)
+            || person
+                .slug
+                .as_deref()
+                .is_some_and(|slug| slug.eq_ignore_ascii_case(username))
+            || self
+                .config
+                .email
+                .as_deref()
+                .zip(person.email_address.as_deref())
+                .is_some_and(|(email, address)| email.eq_ignore_ascii_case(address))
+    }
+
+    async fn fetch_page<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+        start: i64,
+    ) -> Result<BitbucketServerPage<T>, ProviderError> {
+        let endpoint = format!("{}/rest/api/1.0{}", self.config.server(), path);
+
+        let request = self
+            .client
+            .get(endpoint)
+            .query(query)
+            .query(&[("limit", "100".to_string()), ("start", start.to_string())])
+            .bearer_auth(self.config.token())
+            .header("accept", "application/json");
+        let response = self.client.send(request).await?;
+        let response = self.client.error_for_status(response).await?;
+
+        self.client.json(response).await
+    }
+
+    async fn fetch_all<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<Vec<T>, ProviderError> {
+        let mut values = Vec::new();
+        let mut start = 0;
+        loop {
+            let page: BitbucketServerPage<T> = self.fetch_page(path, query, start).await?;
+            values.extend(page.values);
+            match page.next_page_start {
+                Some(next_page_start) if !page.is_last_page => start = next_page_start,
+                _ => break,
+            }
+        }
+        Ok(values)
+    }
+
+    async fn fetch_commits(
+        &self,
+        repository: &BitbucketServerRepository,
+        start_date: OffsetDateTime,
+    ) -> Result<Vec<BitbucketServerActivity>, ProviderError> {
+        let path = format!(
+            "/projects/{}/repos/{}/commits",
+            repository.project.key, repository.slug
+        );
+        let start_timestamp = start_date.unix_timestamp() * 1000;
+
+        let mut activities = Vec::new();
+        let mut start = 0;
+        loop {
+            let page: BitbucketServerPage<BitbucketServerCommit> = self
+                .fetch_page(&path, &[("merges", "exclude")], start)
+                .await?;
+
+            // Commits are listed newest first, stop paging once the window is left.
+            let mut reached_start = false;
+            for commit in page.values {
+                if commit.author_timestamp < start_timestamp {
+                    reached_start = true;
+                } else if self.is_user(&commit.author) {
+                    activities.push(BitbucketServerActivity {
+                        timestamp: commit.author_timestamp,
+                    });
+                }
+            }
+
+            match page.next_page_start {
+                Some(next_page_start) if !page.is_last_page && !reached_start => {
+                    start = next_page_start
+                }
+                _ => break,
+            }
+        }
+        Ok(activities)
+    }
+
+    async fn fetch_pull_requests(&self) -> Result<Vec<BitbucketServerActivity>, ProviderError> {
+        let authored: Vec<BitbucketServerPullRequest> = self
+            .fetch_all("/dashboard/pull-requests", &[("role", "AUTHOR")])
+            .await?;
+        let reviewed: Vec<BitbucketServerPullRequest> = self
+            .fetch_all("/dashboard/pull-requests", &[("role", "REVIEWER")])
+            .await?;
+
+        let created = authored
+            .into_iter()
+            .map(|pull_request| BitbucketServerActivity {
+                timestamp: pull_request.created_date,
+            });
+        // Review verdicts carry no timestamp of their own, so the last update of
+        // the pull request is the closest approximation.
+        let reviews = reviewed
+            .into_iter()
+            .filter(|pull_request| {
+                pull_request
+                    .reviewers
+                    .iter()
+                    .any(|reviewer| self.is_user(&reviewer.user) && reviewer.status != "UNAPPROVED")
+            })
+            .map(|pull_request| BitbucketServerActivity {
+                timestamp: pull_request.updated_date,
+            });
+
+        Ok(created.chain(reviews).collect())
+    }
+}
+
+impl Provider for BitbucketServerClient {
+    fn name(&self) -> &str {
+        "Bitbucket Server"
+    }
+
+    async fn from_env(prefix: &str) -> Result<Self, ProviderError> {
+        Ok(Self::new(BitbucketServerConfig::from_env(prefix).await?))
+    }
+
+    fn fetch_events(
+        &self,
+        start_date: OffsetDateTime,
+        _end_date: OffsetDateTime,
+    ) -> BoxFuture<'_, Result<Events, ProviderError>> {
+        Box::pin(async move {
+            let repositories: Vec<BitbucketServerRepository> =
+                self.fetch_all("/repos", &[]).await?;
+
+            let mut events = Vec::new();
+            for repository in &repositories {
+                events.extend(self.fetch_commits(repository, start_date).await?);
+            }
+            events.extend(self.fetch_pull_requests().await?);
+            Ok(into_events(events))
+        })
+    }
+
+    fn quota(&self) -> Option<Quota> {
+        self.client.quota()
+    }
+}
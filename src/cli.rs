@@ -0,0 +1,140 @@
+use crate::colors::{ColorScheme, SCHEMES};
+use crate::config;
+use crate::provider::{ProviderEntry, REGISTRY};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use time::Date;
+use time::format_description::well_known::Iso8601;
+
+/// Shows your contribution graph of several Git servers combined.
+///
+/// Options override the corresponding settings of `.env`, the environment and the
+/// configuration file.
+#[derive(Debug, Parser)]
+#[command(version)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    #[command(flatten)]
+    pub options: Options,
+}
+
+#[derive(Debug, Clone, Copy, Subcommand)]
+pub enum Command {
+    /// Show the contribution graph, which is the default.
+    Show,
+    /// List the available color schemes.
+    ListSchemes,
+    /// List the providers, the prefix of their settings and their configured accounts.
+    Providers,
+    /// Check every configured account by fetching its contributions of the last week.
+    Doctor,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Format {
+    /// The contribution graph.
+    Graph,
+    /// The contributions per day and the status of every provider, as JSON.
+    Json,
+}
+
+#[derive(Debug, Args)]
+pub struct Options {
+    /// First day of the graph, such as 2025-01-31 [default: a year before the last day]
+    #[arg(long, global = true, value_name = "DATE", value_parser = parse_date)]
+    pub from: Option<Date>,
+
+    /// Last day of the graph, such as 2025-12-31 [default: today]
+    #[arg(long, global = true, value_name = "DATE", value_parser = parse_date)]
+    pub to: Option<Date>,
+
+    /// Color scheme of the graph, see `list-schemes` [default: github]
+    #[arg(long, global = true, value_name = "NAME", value_parser = parse_color_scheme)]
+    pub color_scheme: Option<&'static ColorScheme>,
+
+    /// What to output
+    #[arg(long, global = true, value_enum, default_value_t = Format::Graph)]
+    pub format: Format,
+
+    /// Only include this provider, such as `gitlab`, or account, such as `gitlab.work`. Can be
+    /// given several times
+    #[arg(short, long = "provider", global = true, value_name = "PROVIDER", value_parser = parse_provider)]
+    pub providers: Vec<ProviderFilter>,
+
+    /// Also list the providers that are not configured, and what caused failures
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+
+    /// Only show the graph when every configured provider could be fetched
+    #[arg(long, global = true)]
+    pub strict: bool,
+}
+
+/// A provider, or one of its accounts, to include.
+#[derive(Debug, Clone)]
+pub struct ProviderFilter {
+    prefix: &'static str,
+    account: Option<String>,
+}
+
+impl Options {
+    /// Whether any account of `entry` is included.
+    pub fn includes_provider(&self, entry: &ProviderEntry) -> bool {
+        self.providers.is_empty()
+            || self
+                .providers
+                .iter()
+                .any(|filter| filter.prefix == entry.prefix)
+    }
+
+    /// Whether the account called `account` (or the unnamed account) of `entry` is included.
+    pub fn includes(&self, entry: &ProviderEntry, account: Option<&str>) -> bool {
+        self.providers.is_empty()
+            || self.providers.iter().any(|filter| {
+                filter.prefix == entry.prefix
+                    && match (&filter.account, account) {
+                        (None, _) => true,
+                        (Some(wanted), Some(account)) => {
+                            config::variable(entry.prefix, wanted)
+                                == config::variable(entry.prefix, account)
+                        }
+                        (Some(_), None) => false,
+                    }
+            })
+    }
+}
+
+fn parse_date(value: &str) -> Result<Date, String> {
+    Date::parse(value, &Iso8601::DATE).map_err(|_| "expected a date such as 2025-01-31".to_string())
+}
+
+fn parse_color_scheme(value: &str) -> Result<&'static ColorScheme, String> {
+    ColorScheme::find_by_name(value).ok_or_else(|| {
+        let names: Vec<&str> = SCHEMES.iter().map(|scheme| scheme.name).collect();
+        format!("expected one of {}", names.join(", "))
+    })
+}
+
+/// Parses `gitlab` or `gitlab.work`, by the prefix or name of the provider.
+fn parse_provider(value: &str) -> Result<ProviderFilter, String> {
+    let (provider, account) = match value.split_once('.') {
+        Some((provider, account)) => (provider, Some(account.to_string())),
+        None => (value, None),
+    };
+    let entry = REGISTRY
+        .iter()
+        .find(|entry| {
+            entry.prefix.eq_ignore_ascii_case(provider) || entry.name.eq_ignore_ascii_case(provider)
+        })
+        .ok_or_else(|| {
+            let prefixes: Vec<String> = REGISTRY
+                .iter()
+                .map(|entry| entry.prefix.to_lowercase())
+                .collect();
+            format!("expected one of {}", prefixes.join(", "))
+        })?;
+    Ok(ProviderFilter {
+        prefix: entry.prefix,
+        account,
+    })
+}
@@ -111,7 +111,6 @@ impl ColorScheme {
         SCHEMES.iter().find(|scheme| scheme.name == name)
     }
 
-    #[allow(dead_code)]
     pub fn default() -> &'static ColorScheme {
         &SCHEMES[0] // GitHub colors as default
     }
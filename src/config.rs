@@ -0,0 +1,451 @@
+//! Settings from the configuration file, `$XDG_CONFIG_HOME/multi-git-graph/config.toml` by
+//! default. Every setting corresponds to a variable, such as `token` under `[gitlab]` to
+//! `GITLAB_TOKEN`, and variables from the environment or `.env` take precedence over the file.
+
+use crate::colors::ColorScheme;
+use crate::errors::SettingsError;
+use crate::provider::{ProviderEntry, REGISTRY};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use time::Date;
+use time::format_description::well_known::Iso8601;
+use toml::{Table, Value};
+
+/// Variable pointing at a configuration file to use instead of the default one.
+const FILE_VARIABLE: &str = "MULTI_GIT_GRAPH_CONFIG";
+
+/// Settings outside of the provider tables, with the variable each one corresponds to.
+const SETTINGS: &[(&str, &str)] = &[
+    ("color_scheme", "COLOR_SCHEME"),
+    ("timeout", "PROVIDER_TIMEOUT"),
+    ("start_date", "START_DATE"),
+    ("end_date", "END_DATE"),
+    ("verbose", "VERBOSE"),
+    ("strict", "STRICT"),
+];
+
+/// Provider settings holding a list of paths, which are joined like `PATH` rather than with
+/// commas.
+const PATH_LISTS: &[&str] = &["repositories"];
+
+struct Setting {
+    /// Where the value was set, such as `gitlab.accounts.work.token`.
+    key: String,
+    value: String,
+}
+
+#[derive(Default)]
+struct Config {
+    path: PathBuf,
+    /// Settings by the variable they correspond to.
+    settings: HashMap<String, Setting>,
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// The configuration file in use, `$XDG_CONFIG_HOME/multi-git-graph/config.toml` unless
+/// `MULTI_GIT_GRAPH_CONFIG` points elsewhere. Whether it has to exist is returned with it.
+fn config_path() -> Option<(PathBuf, bool)> {
+    if let Ok(path) = dotenv::var(FILE_VARIABLE) {
+        return Some((PathBuf::from(path), true));
+    }
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some((
+        config_home.join("multi-git-graph").join("config.toml"),
+        false,
+    ))
+}
+
+/// Reads the configuration file, if there is one. Call this once, before reading any settings.
+pub fn load() -> Result<(), SettingsError> {
+    let Some((path, required)) = config_path() else {
+        return Ok(());
+    };
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound && !required => {
+            return Ok(());
+        }
+        Err(source) => {
+            return Err(SettingsError::ReadError {
+                path: path.display().to_string(),
+                source,
+            });
+        }
+    };
+    let table: Table = toml::from_str(&contents).map_err(|source| SettingsError::SyntaxError {
+        path: path.display().to_string(),
+        source,
+    })?;
+
+    let mut config = Config {
+        path,
+        ..Config::default()
+    };
+    config.read(table)?;
+    let _ = CONFIG.set(config);
+    Ok(())
+}
+
+impl Config {
+    fn invalid(&self, key: &str, message: &str) -> SettingsError {
+        SettingsError::InvalidValue {
+            key: format!("`{}` in {}", key, self.path.display()),
+            message: message.to_string(),
+        }
+    }
+
+    fn insert(&mut self, variable: String, key: String, value: String) {
+        self.settings.insert(variable, Setting { key, value });
+    }
+
+    fn read(&mut self, table: Table) -> Result<(), SettingsError> {
+        for (key, value) in table {
+            if let Some(&(_, variable)) = SETTINGS.iter().find(|(name, _)| *name == key) {
+                let value = self.setting(&key, value)?;
+                self.insert(variable.to_string(), key, value);
+            } else if let Some(entry) = REGISTRY
+                .iter()
+                .find(|entry| entry.prefix.to_lowercase() == key)
+            {
+                let Value::Table(table) = value else {
+                    return Err(self.invalid(&key, "must be a table of provider settings"));
+                };
+                self.read_provider(entry, &key, table)?;
+            } else {
+                let providers: Vec<String> = REGISTRY
+                    .iter()
+                    .map(|entry| entry.prefix.to_lowercase())
+                    .collect();
+                return Err(self.invalid(
+                    &key,
+                    &format!(
+                        "is not a known setting or provider, providers are {}",
+                        providers.join(", ")
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks one of the [`SETTINGS`], since nothing else validates them before they are used.
+    fn setting(&self, key: &str, value: Value) -> Result<String, SettingsError> {
+        match (key, value) {
+            ("color_scheme", Value::String(name)) => {
+                if ColorScheme::find_by_name(&name).is_none() {
+                    return Err(self.invalid(key, &format!("names unknown color scheme {}", name)));
+                }
+                Ok(name)
+            }
+            ("timeout", Value::Integer(seconds)) if seconds > 0 => Ok(seconds.to_string()),
+            ("timeout", _) => Err(self.invalid(key, "must be a positive number of seconds")),
+            ("start_date" | "end_date", Value::Datetime(datetime))
+                if datetime.date.is_some() && datetime.time.is_none() =>
+            {
+                Ok(datetime.to_string())
+            }
+            ("start_date" | "end_date", Value::String(date)) if parse_date(&date).is_some() => {
+                Ok(date)
+            }
+            ("start_date" | "end_date", _) => {
+                Err(self.invalid(key, "must be a date such as 2025-01-31"))
+            }
+            ("verbose" | "strict", Value::Boolean(enabled)) => Ok(enabled.to_string()),
+            ("verbose" | "strict", _) => Err(self.invalid(key, "must be true or false")),
+            (_, _) => Err(self.invalid(key, "must be a string")),
+        }
+    }
+
+    /// Reads a provider table, such as `[gitlab]`, and its named accounts, such as
+    /// `[gitlab.accounts.work]`.
+    fn read_provider(
+        &mut self,
+        entry: &ProviderEntry,
+        key: &str,
+        table: Table,
+    ) -> Result<(), SettingsError> {
+        let prefix = entry.prefix;
+        for (name, value) in table {
+            let setting_key = format!("{}.{}", key, name);
+            if name != "accounts" {
+                self.check_provider_setting(entry, &setting_key, &name)?;
+                let value = self.provider_value(&setting_key, &name, value)?;
+                self.insert(variable(prefix, &name), setting_key, value);
+                continue;
+            }
+
+            let Value::Table(accounts) = value else {
+                return Err(self.invalid(&setting_key, "must be a table of accounts"));
+            };
+            let names = accounts.keys().cloned().collect::<Vec<_>>().join(",");
+            self.insert(variable(prefix, "ACCOUNTS"), setting_key.clone(), names);
+
+            for (account, settings) in accounts {
+                let account_key = format!("{}.{}", setting_key, account);
+                let Value::Table(settings) = settings else {
+                    return Err(self.invalid(&account_key, "must be a table of account settings"));
+                };
+                let account_prefix = variable(prefix, &account);
+                for (name, value) in settings {
+                    let setting_key = format!("{}.{}", account_key, name);
+                    self.check_provider_setting(entry, &setting_key, &name)?;
+                    let value = self.provider_value(&setting_key, &name, value)?;
+                    self.insert(variable(&account_prefix, &name), setting_key, value);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that `name` is one of the settings of the provider, as nothing would read it
+    /// otherwise.
+    fn check_provider_setting(
+        &self,
+        entry: &ProviderEntry,
+        key: &str,
+        name: &str,
+    ) -> Result<(), SettingsError> {
+        let known = |setting: &str| variable(entry.prefix, name) == variable(entry.prefix, setting);
+        if known("TIMEOUT") || entry.setting_names().any(|setting| known(&setting)) {
+            return Ok(());
+        }
+        let settings: Vec<String> = entry
+            .settings
+            .iter()
+            .chain(entry.secrets)
+            .chain(&["TIMEOUT"])
+            .map(|setting| setting.to_lowercase())
+            .collect();
+        Err(self.invalid(
+            key,
+            &format!(
+                "is not a {} setting, which are {}",
+                entry.name,
+                settings.join(", ")
+            ),
+        ))
+    }
+
+    /// Turns a provider setting into the value of its variable. Lists are joined with commas.
+    fn provider_value(&self, key: &str, name: &str, value: Value) -> Result<String, SettingsError> {
+        // A provider would only find out about an unusable timeout once it is fetched.
+        if name.eq_ignore_ascii_case("timeout") {
+            return match value {
+                Value::Integer(seconds) if seconds > 0 => Ok(seconds.to_string()),
+                _ => Err(self.invalid(key, "must be a positive number of seconds")),
+            };
+        }
+        let scalar = |value: Value| match value {
+            Value::String(value) => Some(value),
+            Value::Integer(value) => Some(value.to_string()),
+            Value::Float(value) => Some(value.to_string()),
+            Value::Boolean(value) => Some(value.to_string()),
+            Value::Datetime(value) => Some(value.to_string()),
+            Value::Array(_) | Value::Table(_) => None,
+        };
+
+        match value {
+            Value::Array(values) => {
+                let values: Vec<String> = values
+                    .into_iter()
+                    .map(scalar)
+                    .collect::<Option<_>>()
+                    .ok_or_else(|| self.invalid(key, "must be a list of plain values"))?;
+                if !PATH_LISTS.contains(&name) {
+                    return Ok(values.join(","));
+                }
+                std::env::join_paths(values)
+                    .map(|paths| paths.to_string_lossy().into_owned())
+                    .map_err(|e| self.invalid(key, &e.to_string()))
+            }
+            value => scalar(value)
+                .ok_or_else(|| self.invalid(key, "must be a string, number, boolean or list")),
+        }
+    }
+}
+
+/// The variable a setting corresponds to, such as `GITLAB_WORK` for `work` below `GITLAB`.
+pub fn variable(prefix: &str, name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}_{}", prefix, name)
+}
+
+fn parse_date(date: &str) -> Option<Date> {
+    Date::parse(date.trim(), &Iso8601::DATE).ok()
+}
+
+/// Reads a setting from the environment or `.env`, or else from the configuration file.
+pub fn var(variable: &str) -> Option<String> {
+    dotenv::var(variable).ok().or_else(|| {
+        CONFIG
+            .get()?
+            .settings
+            .get(variable)
+            .map(|setting| setting.value.clone())
+    })
+}
+
+/// Reads a boolean setting, such as `VERBOSE=true`.
+pub fn flag(variable: &str) -> bool {
+    var(variable).is_some_and(|value| value == "true" || value == "1")
+}
+
+/// Reads a date setting, such as `START_DATE=2025-01-31`.
+pub fn date(variable: &str) -> Result<Option<Date>, SettingsError> {
+    let Some(value) = var(variable) else {
+        return Ok(None);
+    };
+    parse_date(&value)
+        .map(Some)
+        .ok_or_else(|| SettingsError::InvalidValue {
+            key: describe(variable),
+            message: "must be a date such as 2025-01-31".to_string(),
+        })
+}
+
+/// Names where a setting was set, so errors point at the right place: the variable itself,
+/// or its key in the configuration file.
+pub fn describe(variable: &str) -> String {
+    if dotenv::var(variable).is_err()
+        && let Some(config) = CONFIG.get()
+        && let Some(setting) = config.settings.get(variable)
+    {
+        return format!("`{}` in {}", setting.key, config.path.display());
+    }
+    variable.to_string()
+}
+
+/// The configuration file that was read, if any.
+pub fn path() -> Option<&'static Path> {
+    CONFIG.get().map(|config| config.path.as_path())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(contents: &str) -> Result<Config, SettingsError> {
+        let mut config = Config {
+            path: PathBuf::from("config.toml"),
+            ..Config::default()
+        };
+        config.read(toml::from_str(contents).unwrap())?;
+        Ok(config)
+    }
+
+    fn value<'a>(config: &'a Config, variable: &str) -> Option<&'a str> {
+        config
+            .settings
+            .get(variable)
+            .map(|setting| setting.value.as_str())
+    }
+
+    fn error(contents: &str) -> String {
+        match read(contents) {
+            Ok(_) => panic!("{} was accepted", contents),
+            Err(error) => error.to_string(),
+        }
+    }
+
+    #[test]
+    fn read_maps_settings_to_variables() {
+        let config = read(
+            r#"
+            color_scheme = "github"
+            timeout = 10
+            start_date = 2025-01-31
+
+            [gitlab]
+            server = "https://gitlab.example.com"
+            weight_pushed = 2
+
+            [gitlab.accounts.work]
+            token = "secret"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(value(&config, "COLOR_SCHEME"), Some("github"));
+        assert_eq!(value(&config, "PROVIDER_TIMEOUT"), Some("10"));
+        assert_eq!(value(&config, "START_DATE"), Some("2025-01-31"));
+        assert_eq!(
+            value(&config, "GITLAB_SERVER"),
+            Some("https://gitlab.example.com")
+        );
+        assert_eq!(value(&config, "GITLAB_WEIGHT_PUSHED"), Some("2"));
+        assert_eq!(value(&config, "GITLAB_ACCOUNTS"), Some("work"));
+        assert_eq!(value(&config, "GITLAB_WORK_TOKEN"), Some("secret"));
+        assert_eq!(
+            config.settings["GITLAB_WORK_TOKEN"].key,
+            "gitlab.accounts.work.token"
+        );
+    }
+
+    #[test]
+    fn read_joins_lists() {
+        let config = read(
+            r#"
+            [local]
+            repositories = ["/src", "/work"]
+            author_emails = ["me@example.com", "me@work.example.com"]
+            "#,
+        )
+        .unwrap();
+        let paths = std::env::join_paths(["/src", "/work"]).unwrap();
+        assert_eq!(value(&config, "LOCAL_REPOSITORIES"), paths.to_str());
+        assert_eq!(
+            value(&config, "LOCAL_AUTHOR_EMAILS"),
+            Some("me@example.com,me@work.example.com")
+        );
+    }
+
+    #[test]
+    fn read_rejects_unknown_keys() {
+        assert!(error("colour_scheme = \"github\"").starts_with("`colour_scheme` in config.toml"));
+        assert!(
+            error("[gitlab]\ntokn = \"secret\"")
+                .starts_with("`gitlab.tokn` in config.toml is not a GitLab setting")
+        );
+        assert!(
+            error("[gitlab.accounts.work]\nworkspace = \"me\"")
+                .starts_with("`gitlab.accounts.work.workspace` in config.toml is not a GitLab")
+        );
+    }
+
+    #[test]
+    fn read_rejects_unusable_values() {
+        assert_eq!(
+            error("color_scheme = \"plaid\""),
+            "`color_scheme` in config.toml names unknown color scheme plaid"
+        );
+        assert_eq!(
+            error("timeout = 0"),
+            "`timeout` in config.toml must be a positive number of seconds"
+        );
+        assert_eq!(
+            error("[gitlab]\ntimeout = \"abc\""),
+            "`gitlab.timeout` in config.toml must be a positive number of seconds"
+        );
+        assert_eq!(
+            error("[gitlab.accounts.work]\ntimeout = -5"),
+            "`gitlab.accounts.work.timeout` in config.toml must be a positive number of seconds"
+        );
+        assert_eq!(
+            error("end_date = \"tomorrow\""),
+            "`end_date` in config.toml must be a date such as 2025-01-31"
+        );
+    }
+}
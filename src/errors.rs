@@ -1,11 +1,116 @@
+use reqwest::StatusCode;
+use std::fmt;
+
+/// Where a failed HTTP request went and what came back, with credentials redacted from the URL.
+#[derive(Debug)]
+pub struct HttpContext {
+    pub provider: String,
+    pub url: String,
+    pub status: Option<StatusCode>,
+    /// The start of the response body.
+    pub body: Option<String>,
+}
+
+impl fmt::Display for HttpContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} request to {}", self.provider, self.url)?;
+        if let Some(status) = self.status {
+            write!(f, " returned {}", status)?;
+        }
+        if let Some(body) = &self.body {
+            write!(f, ": {}", body)?;
+        }
+        Ok(())
+    }
+}
+
+/// The message of an error followed by those of its causes, skipping causes that are already
+/// part of the message of the error they caused.
+fn chain(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        let cause_message = cause.to_string();
+        if !message.contains(&cause_message) {
+            message.push_str(": ");
+            message.push_str(&cause_message);
+        }
+        source = cause.source();
+    }
+    message
+}
+
+/// A configuration file or setting that cannot be used.
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, thiserror::Error)]
+pub enum SettingsError {
+    #[error("Failed to read {path}: {source}")]
+    ReadError {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("{path} is not valid TOML: {source}")]
+    SyntaxError {
+        path: String,
+        #[source]
+        source: toml::de::Error,
+    },
+    #[error("{key} {message}")]
+    InvalidValue { key: String, message: String },
+}
+
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, thiserror::Error)]
 pub enum ProviderError {
     #[error("Configuration error: {0}")]
     ConfigError(String),
     #[error("HTTP request failed: {0}")]
     RequestError(#[from] reqwest::Error),
+    #[error("Could not reach the server: {context}: {}", chain(source))]
+    ConnectionError {
+        context: HttpContext,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("Authentication failed, check the token: {0}")]
+    AuthError(HttpContext),
+    #[error("Not found, check the username and server: {0}")]
+    NotFoundError(HttpContext),
+    #[error("Rate limit exceeded: {0}")]
+    RateLimitError(HttpContext),
+    #[error("Request failed: {0}")]
+    StatusError(HttpContext),
+    #[error("Unexpected response, {source}: {context}")]
+    SchemaError {
+        context: HttpContext,
+        #[source]
+        source: serde_json::Error,
+    },
     #[error("Failed to parse response: {0}")]
     ParseError(#[from] serde_json::Error),
     #[error("Date parsing error: {0}")]
     DateError(String),
+    #[error("Unsupported server: {0}")]
+    UnsupportedServer(String),
+    #[error("API error: {0}")]
+    ApiError(String),
+    #[error("I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Failed to read {path}: {source}")]
+    ReadError {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("{path} is not valid JSON: {source}")]
+    SyntaxError {
+        path: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("Command failed: {0}")]
+    CommandError(String),
+    #[error("Fetching stopped unexpectedly: {0}")]
+    TaskError(#[from] tokio::task::JoinError),
 }
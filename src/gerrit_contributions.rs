@@ -0,0 +1,258 @@
+use crate::{
+    errors::ProviderError,
+    http::{HttpClient, Quota},
+    processor::Event,
+    provider::{
+        BoxFuture, Events, GenericConfig, Provider, ProviderConfig, into_events, weight_var,
+    },
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use time::{OffsetDateTime, PrimitiveDateTime};
+
+/// Prefix Gerrit puts in front of every JSON response to prevent XSSI.
+const XSSI_PREFIX: &str = ")]}'";
+const PAGE_SIZE: usize = 100;
+
+/// How much each kind of Gerrit activity counts towards the graph.
+#[derive(Debug)]
+struct GerritWeights {
+    created: i64,
+    patchset: i64,
+    merged: i64,
+    review: i64,
+}
+
+impl GerritWeights {
+    fn from_env(prefix: &str) -> Result<Self, ProviderError> {
+        Ok(Self {
+            created: weight_var(prefix, "WEIGHT_CREATED", 1)?,
+            patchset: weight_var(prefix, "WEIGHT_PATCHSET", 1)?,
+            merged: weight_var(prefix, "WEIGHT_MERGED", 1)?,
+            review: weight_var(prefix, "WEIGHT_REVIEW", 1)?,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct GerritConfig {
+    generic: GenericConfig,
+    weights: GerritWeights,
+}
+
+impl GerritConfig {
+    async fn from_env(prefix: &str) -> Result<Self, ProviderError> {
+        Ok(Self {
+            generic: GenericConfig::from_env(prefix).await?,
+            weights: GerritWeights::from_env(prefix)?,
+        })
+    }
+}
+
+impl ProviderConfig for GerritConfig {
+    fn server(&self) -> &str {
+        self.generic.server()
+    }
+    fn username(&self) -> &str {
+        self.generic.username()
+    }
+    fn token(&self) -> &str {
+        self.generic.token()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GerritAccount {
+    username: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GerritRevision {
+    #[serde(rename = "_number")]
+    number: i64,
+    created: String,
+    uploader: Option<GerritAccount>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GerritMessage {
+    author: Option<GerritAccount>,
+    date: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GerritChange {
+    created: String,
+    status: String,
+    submitted: Option<String>,
+    #[serde(default)]
+    revisions: HashMap<String, GerritRevision>,
+    #[serde(default)]
+    messages: Vec<GerritMessage>,
+    #[serde(rename = "_more_changes", default)]
+    more_changes: bool,
+}
+
+#[derive(Debug)]
+struct GerritActivity {
+    timestamp: String,
+    weight: i64,
+}
+
+impl Event for GerritActivity {
+    fn timestamp(&self) -> Result<OffsetDateTime, ProviderError> {
+        // Gerrit timestamps look like `2024-01-31 12:34:56.000000000` and are always UTC.
+        let format = time::format_description::parse(
+            "[year]-[month]-[day] [hour]:[minute]:[second].[subsecond]",
+        )
+        .map_err(|e| ProviderError::DateError(format!("Invalid date format: {}", e)))?;
+        PrimitiveDateTime::parse(&self.timestamp, &format)
+            .map(PrimitiveDateTime::assume_utc)
+            .map_err(|e| ProviderError::DateError(format!("Failed to parse date: {}", e)))
+    }
+    fn contributions(&self) -> i64 {
+        self.weight
+    }
+}
+
+pub struct GerritClient {
+    client: HttpClient,
+    config: GerritConfig,
+}
+
+impl GerritClient {
+    fn new(config: GerritConfig) -> Self {
+        Self {
+            client: HttpClient::new("Gerrit"),
+            config,
+        }
+    }
+
+    fn is_user(&self, account: Option<&GerritAccount>) -> bool {
+        account
+            .and_then(|account| account.username.as_deref())
+            .is_some_and(|username| username == self.config.username())
+    }
+
+    async fn query_changes(
+        &self,
+        query: &str,
+        option: &str,
+    ) -> Result<Vec<GerritChange>, ProviderError> {
+        let endpoint = format!("{}/a/changes/", self.config.server());
+
+        let mut changes = Vec::new();
+        loop {
+            let request = self
+                .client
+                .get(&endpoint)
+                .query(&[
+                    ("q", query.to_string()),
+                    ("o", option.to_string()),
+                    ("o", "DETAILED_ACCOUNTS".to_string()),
+                    ("n", PAGE_SIZE.to_string()),
+                    ("S", changes.len().to_string()),
+                ])
+                .basic_auth(self.config.username(), Some(self.config.token()))
+                .header("accept", "application/json");
+            let response = self.client.send(request).await?;
+            let response = self.client.error_for_status(response).await?;
+
+            let url = response.url().clone();
+            let body = self.client.text(response).await?;
+            let page: Vec<GerritChange> = self
+                .client
+                .parse_json(&url, body.trim_start_matches(XSSI_PREFIX))?;
+            let more_changes = page.last().is_some_and(|change| change.more_changes);
+            changes.extend(page);
+            if !more_changes {
+                break;
+            }
+        }
+        Ok(changes)
+    }
+}
+
+impl Provider for GerritClient {
+    fn name(&self) -> &str {
+        "Gerrit"
+    }
+
+    async fn from_env(prefix: &str) -> Result<Self, ProviderError> {
+        Ok(Self::new(GerritConfig::from_env(prefix).await?))
+    }
+
+    fn fetch_events(
+        &self,
+        start_date: OffsetDateTime,
+        _end_date: OffsetDateTime,
+    ) -> BoxFuture<'_, Result<Events, ProviderError>> {
+        Box::pin(async move {
+            let weights = &self.config.weights;
+            let username = self.config.username();
+            let mut events = Vec::new();
+
+            let owned = self
+                .query_changes(
+                    &format!("owner:{} after:\"{}\"", username, start_date.date()),
+                    "ALL_REVISIONS",
+                )
+                .await?;
+            for change in owned {
+                events.push(GerritActivity {
+                    timestamp: change.created,
+                    weight: weights.created,
+                });
+                // The first patchset is uploaded together with the change itself.
+                events.extend(
+                    change
+                        .revisions
+                        .into_values()
+                        .filter(|revision| {
+                            revision.number > 1 && self.is_user(revision.uploader.as_ref())
+                        })
+                        .map(|revision| GerritActivity {
+                            timestamp: revision.created,
+                            weight: weights.patchset,
+                        }),
+                );
+                if let Some(submitted) = change.submitted.filter(|_| change.status == "MERGED") {
+                    events.push(GerritActivity {
+                        timestamp: submitted,
+                        weight: weights.merged,
+                    });
+                }
+            }
+
+            let reviewed = self
+                .query_changes(
+                    &format!(
+                        "reviewedby:{} -owner:{} after:\"{}\"",
+                        username,
+                        username,
+                        start_date.date()
+                    ),
+                    "MESSAGES",
+                )
+                .await?;
+            for change in reviewed {
+                events.extend(
+                    change
+                        .messages
+                        .into_iter()
+                        .filter(|message| self.is_user(message.author.as_ref()))
+                        .map(|message| GerritActivity {
+                            timestamp: message.date,
+                            weight: weights.review,
+                        }),
+                );
+            }
+
+            Ok(into_events(events))
+        })
+    }
+
+    fn quota(&self) -> Option<Quota> {
+        self.client.quota()
+    }
+}
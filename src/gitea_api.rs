@@ -0,0 +1,439 @@
+//! Client shared by the Gitea family of forges (Gitea, Codeberg, Forgejo and Gogs),
+//! which all expose a variation of the same `/api/v1` REST API.
+
+use crate::{
+    errors::ProviderError,
+    http::{HttpClient, Quota},
+    processor::Event,
+    provider::ProviderConfig,
+    types::ContributionKind,
+};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::collections::HashSet;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+const PAGE_SIZE: usize = 50;
+
+/// How contributions are gathered from a server.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum GiteaStrategy {
+    /// The server aggregates contributions itself in `/users/{user}/heatmap`.
+    Heatmap,
+    /// No heatmap is available, so the user's repositories and commits are walked.
+    CommitWalk,
+}
+
+impl GiteaStrategy {
+    /// Picks a strategy for the version string reported by `/api/v1/version`.
+    ///
+    /// Forgejo reports versions such as `7.0.0+gitea-1.22.0` and always has the
+    /// heatmap, Gitea only has it since 1.7 and Gogs (0.x) never had it.
+    fn for_version(version: &str) -> Result<Self, ProviderError> {
+        if version.contains("+gitea-") {
+            return Ok(Self::Heatmap);
+        }
+
+        let mut parts = version
+            .trim_start_matches('v')
+            .split(|c: char| !c.is_ascii_digit())
+            .map(|part| part.parse::<u64>());
+        match (parts.next(), parts.next()) {
+            (Some(Ok(0)), _) | (Some(Ok(1)), Some(Ok(0..=6))) => Ok(Self::CommitWalk),
+            (Some(Ok(_)), _) => Ok(Self::Heatmap),
+            _ => Err(ProviderError::UnsupportedServer(format!(
+                "unrecognised version \"{}\"",
+                version
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaVersion {
+    version: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaRepository {
+    full_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaUser {
+    login: Option<String>,
+    username: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaSignature {
+    name: String,
+    date: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaCommitDetails {
+    author: GiteaSignature,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaCommit {
+    commit: GiteaCommitDetails,
+    author: Option<GiteaUser>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaActivity {
+    op_type: String,
+    #[serde(default)]
+    content: String,
+    created: String,
+}
+
+/// The content of a `commit_repo` activity, describing the pushed commits.
+#[derive(Debug, Deserialize)]
+struct GiteaPushCommits {
+    #[serde(rename = "Len")]
+    len: i64,
+}
+
+impl GiteaActivity {
+    fn kind(&self) -> Option<ContributionKind> {
+        match self.op_type.as_str() {
+            "commit_repo" => Some(ContributionKind::Commit),
+            "create_pull_request"
+            | "merge_pull_request"
+            | "auto_merge_pull_request"
+            | "close_pull_request"
+            | "reopen_pull_request"
+            | "pull_request_ready_for_review" => Some(ContributionKind::PullRequest),
+            "create_issue" | "close_issue" | "reopen_issue" => Some(ContributionKind::Issue),
+            "approve_pull_request" | "reject_pull_request" | "pull_review_dismissed" => {
+                Some(ContributionKind::Review)
+            }
+            "comment_issue" | "comment_pull" => Some(ContributionKind::Comment),
+            _ => None,
+        }
+    }
+
+    /// Pushes count once per commit, every other action counts once.
+    fn contributions(&self) -> i64 {
+        if self.op_type != "commit_repo" {
+            return 1;
+        }
+        serde_json::from_str::<GiteaPushCommits>(&self.content)
+            .map_or(1, |commits| commits.len.max(1))
+    }
+}
+
+/// A number of contributions at a point in time, the shape of a heatmap entry.
+#[derive(Debug, Deserialize)]
+pub struct GiteaEvent {
+    timestamp: i64,
+    contributions: i64,
+    /// Heatmap entries aggregate all kinds of actions.
+    #[serde(skip)]
+    kind: Option<ContributionKind>,
+}
+
+impl Event for GiteaEvent {
+    fn timestamp(&self) -> Result<OffsetDateTime, ProviderError> {
+        OffsetDateTime::from_unix_timestamp(self.timestamp)
+            .map_err(|e| ProviderError::DateError(format!("Failed to parse date: {}", e)))
+    }
+    fn contributions(&self) -> i64 {
+        self.contributions
+    }
+    fn kind(&self) -> Option<ContributionKind> {
+        self.kind
+    }
+}
+
+pub struct GiteaClient<C: ProviderConfig> {
+    client: HttpClient,
+    config: C,
+}
+
+impl<C: ProviderConfig> GiteaClient<C> {
+    pub fn new(provider: &str, config: C) -> Self {
+        Self {
+            client: HttpClient::new(provider),
+            config,
+        }
+    }
+
+    pub fn quota(&self) -> Option<Quota> {
+        self.client.quota()
+    }
+
+    /// Sends an authenticated GET request to `path` below `/api/v1`.
+    async fn get(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<reqwest::Response, ProviderError> {
+        let endpoint = format!("{}/api/v1{}", self.config.server(), path);
+
+        let request = self
+            .client
+            .get(endpoint)
+            .query(query)
+            .header("Authorization", format!("token {}", self.config.token()))
+            .header("accept", "application/json");
+        self.client.send(request).await
+    }
+
+    /// Like [`Self::get`], but returns `None` when the server does not know the endpoint.
+    async fn get_optional(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<Option<reqwest::Response>, ProviderError> {
+        let response = self.get(path, query).await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(Some(self.client.error_for_status(response).await?))
+    }
+
+    async fn probe(&self) -> Result<GiteaStrategy, ProviderError> {
+        // Gogs does not implement the version endpoint at all.
+        let Some(response) = self.get_optional("/version", &[]).await? else {
+            return Ok(GiteaStrategy::CommitWalk);
+        };
+
+        let body = self.client.text(response).await?;
+        let version: GiteaVersion = serde_json::from_str(&body).map_err(|_| {
+            ProviderError::UnsupportedServer(format!(
+                "{} does not look like a Gitea, Forgejo or Gogs server",
+                self.config.server()
+            ))
+        })?;
+        GiteaStrategy::for_version(&version.version)
+    }
+
+    async fn fetch_heatmap(&self) -> Result<Option<Vec<GiteaEvent>>, ProviderError> {
+        let path = format!("/users/{}/heatmap", self.config.username());
+        match self.get_optional(&path, &[]).await? {
+            Some(response) => Ok(Some(self.client.json(response).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Walks the user's activity feed back to `start_date`. Returns `None` when the server
+    /// has no activity feeds, which Gitea only added in 1.19.
+    async fn fetch_activities(
+        &self,
+        start_date: OffsetDateTime,
+        end_date: OffsetDateTime,
+    ) -> Result<Option<Vec<GiteaEvent>>, ProviderError> {
+        let path = format!("/users/{}/activities/feeds", self.config.username());
+
+        let mut events = Vec::new();
+        for page in 1.. {
+            let query = [
+                ("only-performed-by", "true".to_string()),
+                ("page", page.to_string()),
+                ("limit", PAGE_SIZE.to_string()),
+            ];
+            let Some(response) = self.get_optional(&path, &query).await? else {
+                return Ok(None);
+            };
+            let activities: Vec<GiteaActivity> = self.client.json(response).await?;
+            let page_size = activities.len();
+
+            // The feed is listed newest first, stop paging once the window is left.
+            let mut reached_start = false;
+            for activity in activities {
+                let date = OffsetDateTime::parse(&activity.created, &Rfc3339).map_err(|e| {
+                    ProviderError::DateError(format!("Failed to parse date: {}", e))
+                })?;
+                if date < start_date {
+                    reached_start = true;
+                } else if date < end_date {
+                    events.push(GiteaEvent {
+                        timestamp: date.unix_timestamp(),
+                        contributions: activity.contributions(),
+                        kind: activity.kind(),
+                    });
+                }
+            }
+            if reached_start || page_size < PAGE_SIZE {
+                break;
+            }
+        }
+        Ok(Some(events))
+    }
+
+    async fn fetch_repositories(&self) -> Result<Vec<GiteaRepository>, ProviderError> {
+        let mut seen = HashSet::new();
+        let mut repositories = Vec::new();
+        for page in 1.. {
+            let query = [("page", page.to_string()), ("limit", PAGE_SIZE.to_string())];
+            let response = self
+                .get_optional("/user/repos", &query)
+                .await?
+                .ok_or_else(|| {
+                    ProviderError::UnsupportedServer(format!(
+                        "{} does not expose the user's repositories",
+                        self.config.server()
+                    ))
+                })?;
+            let page: Vec<GiteaRepository> = self.client.json(response).await?;
+
+            // Gogs ignores the pagination parameters and returns every repository on
+            // every page, so stop as soon as a page brings nothing new.
+            let page_size = page.len();
+            let mut new_repositories = 0;
+            for repository in page {
+                if seen.insert(repository.full_name.clone()) {
+                    repositories.push(repository);
+                    new_repositories += 1;
+                }
+            }
+            if new_repositories == 0 || page_size < PAGE_SIZE {
+                break;
+            }
+        }
+        Ok(repositories)
+    }
+
+    fn is_user(&self, commit: &GiteaCommit) -> bool {
+        let username = self.config.username();
+        commit
+            .author
+            .as_ref()
+            .and_then(|author| author.login.as_deref().or(author.username.as_deref()))
+            .unwrap_or(&commit.commit.author.name)
+            .eq_ignore_ascii_case(username)
+    }
+
+    async fn fetch_commits(
+        &self,
+        repository: &GiteaRepository,
+        start_date: OffsetDateTime,
+        end_date: OffsetDateTime,
+    ) -> Result<Vec<GiteaEvent>, ProviderError> {
+        let path = format!("/repos/{}/commits", repository.full_name);
+
+        let mut events = Vec::new();
+        for page in 1.. {
+            let query = [("page", page.to_string()), ("limit", PAGE_SIZE.to_string())];
+            let response = self.get(&path, &query).await?;
+            match response.status() {
+                StatusCode::NOT_FOUND => {
+                    return Err(ProviderError::UnsupportedServer(format!(
+                        "{} does not expose commit history",
+                        self.config.server()
+                    )));
+                }
+                // Empty repositories have no commits to list.
+                StatusCode::CONFLICT => break,
+                _ => {}
+            }
+            let response = self.client.error_for_status(response).await?;
+            let commits: Vec<GiteaCommit> = self.client.json(response).await?;
+            let page_size = commits.len();
+
+            // Commits are listed newest first, stop paging once the window is left.
+            let mut reached_start = false;
+            for commit in commits {
+                let date =
+                    OffsetDateTime::parse(&commit.commit.author.date, &Rfc3339).map_err(|e| {
+                        ProviderError::DateError(format!("Failed to parse date: {}", e))
+                    })?;
+                if date < start_date {
+                    reached_start = true;
+                } else if date < end_date && self.is_user(&commit) {
+                    events.push(GiteaEvent {
+                        timestamp: date.unix_timestamp(),
+                        contributions: 1,
+                        kind: Some(ContributionKind::Commit),
+                    });
+                }
+            }
+            if reached_start || page_size < PAGE_SIZE {
+                break;
+            }
+        }
+        Ok(events)
+    }
+
+    async fn walk_commits(
+        &self,
+        start_date: OffsetDateTime,
+        end_date: OffsetDateTime,
+    ) -> Result<Vec<GiteaEvent>, ProviderError> {
+        let mut events = Vec::new();
+        for repository in self.fetch_repositories().await? {
+            events.extend(
+                self.fetch_commits(&repository, start_date, end_date)
+                    .await?,
+            );
+        }
+        Ok(events)
+    }
+
+    pub async fn fetch_events(
+        &self,
+        start_date: OffsetDateTime,
+        end_date: OffsetDateTime,
+    ) -> Result<Vec<GiteaEvent>, ProviderError> {
+        match self.probe().await? {
+            GiteaStrategy::Heatmap => {
+                // The activity feed tells what each contribution was, the heatmap only how many
+                // there were per day.
+                if let Some(events) = self.fetch_activities(start_date, end_date).await? {
+                    return Ok(events);
+                }
+                match self.fetch_heatmap().await? {
+                    Some(events) => Ok(events),
+                    None => self.walk_commits(start_date, end_date).await,
+                }
+            }
+            GiteaStrategy::CommitWalk => self.walk_commits(start_date, end_date).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strategy(version: &str) -> Option<GiteaStrategy> {
+        GiteaStrategy::for_version(version).ok()
+    }
+
+    #[test]
+    fn forgejo_has_the_heatmap() {
+        assert_eq!(strategy("7.0.0+gitea-1.22.0"), Some(GiteaStrategy::Heatmap));
+        assert_eq!(
+            strategy("11.0.1+gitea-1.22.0"),
+            Some(GiteaStrategy::Heatmap)
+        );
+    }
+
+    #[test]
+    fn gitea_has_the_heatmap_since_1_7() {
+        assert_eq!(strategy("1.22.3"), Some(GiteaStrategy::Heatmap));
+        assert_eq!(strategy("v1.7.0"), Some(GiteaStrategy::Heatmap));
+        assert_eq!(strategy("1.6.4"), Some(GiteaStrategy::CommitWalk));
+        assert_eq!(strategy("v1.0.2"), Some(GiteaStrategy::CommitWalk));
+    }
+
+    #[test]
+    fn gogs_walks_commits() {
+        assert_eq!(strategy("0.13.0"), Some(GiteaStrategy::CommitWalk));
+        assert_eq!(strategy("0.12.3+dev"), Some(GiteaStrategy::CommitWalk));
+    }
+
+    #[test]
+    fn unknown_versions_are_unsupported() {
+        assert!(matches!(
+            GiteaStrategy::for_version("development"),
+            Err(ProviderError::UnsupportedServer(_))
+        ));
+        assert!(strategy("").is_none());
+    }
+}
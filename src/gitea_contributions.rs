@@ -1,101 +1,50 @@
 use crate::{
-    ContributionCollection, ProviderError,
-    processor::{ContributionProcessor, Event},
+    errors::ProviderError,
+    gitea_api::GiteaClient,
+    http::Quota,
+    provider::{BoxFuture, Events, GenericConfig, Provider, into_events},
 };
-use reqwest::Client;
-use serde::Deserialize;
 use time::OffsetDateTime;
 
-#[derive(Debug)]
-struct GiteaConfig {
-    server: String,
-    username: String,
-    token: String,
+/// Gitea, or one of the servers sharing its API: Codeberg, Forgejo and Gogs. They only differ
+/// in their name, which the registry passes to [`GiteaFamilyProvider::load`].
+pub struct GiteaFamilyProvider {
+    name: &'static str,
+    client: GiteaClient<GenericConfig>,
 }
 
-impl GiteaConfig {
-    fn from_env() -> Result<Self, ProviderError> {
+impl GiteaFamilyProvider {
+    /// Loads the account with settings below `prefix` of the member of the family called `name`.
+    pub async fn load(name: &'static str, prefix: &str) -> Result<Self, ProviderError> {
         Ok(Self {
-            server: dotenv::var("GITEA_SERVER")
-                .map_err(|_| ProviderError::ConfigError("GITEA_SERVER must be set".into()))?
-                .trim_end_matches('/')
-                .to_string(),
-            username: dotenv::var("GITEA_USERNAME")
-                .map_err(|_| ProviderError::ConfigError("GITEA_USERNAME must be set".into()))?,
-            token: dotenv::var("GITEA_TOKEN")
-                .map_err(|_| ProviderError::ConfigError("GITEA_TOKEN must be set".into()))?,
+            name,
+            client: GiteaClient::new(name, GenericConfig::from_env(prefix).await?),
         })
     }
 }
 
-#[derive(Debug, Deserialize)]
-struct GiteaEvent {
-    timestamp: i64,
-    contributions: i64,
-}
-
-impl Event for GiteaEvent {
-    fn timestamp(&self) -> Result<OffsetDateTime, ProviderError> {
-        OffsetDateTime::from_unix_timestamp(self.timestamp)
-            .map_err(|e| ProviderError::DateError(format!("Failed to parse date: {}", e)))
+impl Provider for GiteaFamilyProvider {
+    fn name(&self) -> &str {
+        self.name
     }
-    fn contributions(&self) -> i64 {
-        self.contributions
-    }
-}
 
-struct GiteaClient {
-    client: Client,
-    config: GiteaConfig,
-}
-
-impl GiteaClient {
-    fn new(config: GiteaConfig) -> Self {
-        Self {
-            client: Client::new(),
-            config,
-        }
+    async fn from_env(prefix: &str) -> Result<Self, ProviderError> {
+        Self::load("Gitea", prefix).await
     }
 
-    async fn fetch_events(
+    fn fetch_events(
         &self,
-        _start_date: OffsetDateTime,
-        _end_date: OffsetDateTime,
-    ) -> Result<Vec<GiteaEvent>, ProviderError> {
-        let endpoint = format!(
-            "{}/api/v1/users/{}/heatmap",
-            self.config.server, self.config.username
-        );
-
-        let response = self
-            .client
-            .get(endpoint)
-            .header("Authorization", format!("token {}", self.config.token))
-            .header("accept", "application/json")
-            .send()
-            .await?
-            .error_for_status()?;
-
-        let events: Vec<GiteaEvent> = response.json().await?;
-        Ok(events)
+        start_date: OffsetDateTime,
+        end_date: OffsetDateTime,
+    ) -> BoxFuture<'_, Result<Events, ProviderError>> {
+        Box::pin(async move {
+            Ok(into_events(
+                self.client.fetch_events(start_date, end_date).await?,
+            ))
+        })
     }
-}
 
-pub async fn get_gitea_contributions(
-    start_date: OffsetDateTime,
-    end_date: OffsetDateTime,
-) -> Result<ContributionCollection, ProviderError> {
-    let config = GiteaConfig::from_env()?;
-    let client = GiteaClient::new(config);
-    let events = client.fetch_events(start_date, end_date).await?;
-
-    let processor = ContributionProcessor::new(start_date, end_date);
-    let calendar = processor.initialize_contribution_calendar();
-    let (contributions, max_contributions) = processor.process_events(events, calendar)?;
-
-    Ok(ContributionCollection {
-        provider: "Gitea".to_string(),
-        contributions,
-        max_contributions,
-    })
+    fn quota(&self) -> Option<Quota> {
+        self.client.quota()
+    }
 }
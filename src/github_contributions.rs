@@ -1,44 +1,69 @@
 use crate::{
-    ContributionCollection, ProviderError,
-    processor::{ContributionProcessor, Event},
+    ProviderError,
+    http::{HttpClient, Quota},
+    processor::Event,
+    provider::{BoxFuture, Events, Provider, into_events, required_secret, required_var, var},
+    secret::Secret,
+    types::{ContributionBreakdown, KindCounts},
 };
-use reqwest::Client;
 use serde::Deserialize;
-use time::OffsetDateTime;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use time::format_description::well_known::Rfc3339;
+use time::{Duration, OffsetDateTime, Time};
+
+const GITHUB_GRAPHQL_API: &str = "https://api.github.com/graphql";
+
+/// Longest range GitHub returns a contributions collection for.
+const MAX_COLLECTION_SPAN: Duration = Duration::days(365);
+/// Number of repositories the commit contributions are broken down into.
+const BREAKDOWN_REPOSITORIES: usize = 10;
+/// Number of weeks whose breakdown is queried at once, each as its own collection.
+const BREAKDOWN_WEEKS_PER_QUERY: usize = 26;
 
 #[derive(Debug)]
 struct GitHubConfig {
-    token: String,
+    token: Secret,
     username: String,
+    /// GitHub Enterprise Server instance, github.com when not set.
+    server: Option<String>,
 }
 
 impl GitHubConfig {
-    fn from_env() -> Result<Self, ProviderError> {
+    async fn from_env(prefix: &str) -> Result<Self, ProviderError> {
         Ok(Self {
-            token: dotenv::var("GITHUB_TOKEN")
-                .map_err(|_| ProviderError::ConfigError("GITHUB_TOKEN must be set".into()))?,
-            username: dotenv::var("GITHUB_USERNAME")
-                .map_err(|_| ProviderError::ConfigError("GITHUB_USERNAME must be set".into()))?,
+            token: required_secret(prefix, "TOKEN").await?,
+            username: required_var(prefix, "USERNAME")?,
+            server: var(prefix, "SERVER").map(|server| server.trim_end_matches('/').to_string()),
         })
     }
+
+    fn name(&self) -> &'static str {
+        match self.server {
+            Some(_) => "GitHub Enterprise",
+            None => "GitHub",
+        }
+    }
+
+    fn graphql_endpoint(&self) -> String {
+        match &self.server {
+            Some(server) => format!("{}/api/graphql", server),
+            None => GITHUB_GRAPHQL_API.to_string(),
+        }
+    }
 }
 
+// Only fields that every GitHub Enterprise Server version knows are queried.
 const GITHUB_CONTRIBUTIONS_QUERY: &str = r#"
-query($username: String!) {
+query($username: String!, $from: DateTime!, $to: DateTime!) {
     user(login: $username) {
-        name
-        contributionsCollection {
+        contributionsCollection(from: $from, to: $to) {
             contributionCalendar {
-                colors
-                totalContributions
                 weeks {
                     contributionDays {
-                        color
                         contributionCount
                         date
-                        weekday
                     }
-                    firstDay
                 }
             }
         }
@@ -46,57 +71,79 @@ query($username: String!) {
 }
 "#;
 
+const GITHUB_BREAKDOWN_QUERY: &str = r#"
+query($username: String!, $from: DateTime!, $to: DateTime!, $repositories: Int!) {
+    user(login: $username) {
+        contributionsCollection(from: $from, to: $to) {
+            totalCommitContributions
+            totalPullRequestContributions
+            totalIssueContributions
+            totalPullRequestReviewContributions
+            restrictedContributionsCount
+            commitContributionsByRepository(maxRepositories: $repositories) {
+                repository {
+                    nameWithOwner
+                }
+                contributions {
+                    totalCount
+                }
+            }
+        }
+    }
+}
+"#;
+
+/// Fields of the collection of each week, which is queried below an alias such as `week0`.
+const GITHUB_WEEK_FIELDS: &str = "totalCommitContributions totalPullRequestContributions \
+    totalIssueContributions totalPullRequestReviewContributions";
+
 #[derive(Deserialize, Debug)]
-struct GitHubResponse {
-    data: GitHubData,
+struct GitHubResponse<U> {
+    data: Option<GitHubData<U>>,
+    errors: Option<Vec<GitHubError>>,
 }
 
 #[derive(Deserialize, Debug)]
-struct GitHubData {
-    user: GitHubUser,
+struct GitHubError {
+    message: String,
+    /// Such as `NOT_FOUND` or `RATE_LIMITED`.
+    #[serde(rename = "type")]
+    kind: Option<String>,
 }
 
-#[allow(dead_code)]
 #[derive(Deserialize, Debug)]
-struct GitHubUser {
-    name: String,
+struct GitHubData<U> {
+    user: Option<U>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubUser<T> {
     #[serde(rename = "contributionsCollection")]
-    contributions_collection: GitHubContributionsCollection,
+    contributions_collection: T,
 }
 
-#[allow(dead_code)]
 #[derive(Deserialize, Debug)]
 struct GitHubContributionsCollection {
     #[serde(rename = "contributionCalendar")]
     contribution_calendar: GitHubContributionCalendar,
 }
 
-#[allow(dead_code)]
 #[derive(Deserialize, Debug)]
 struct GitHubContributionCalendar {
-    colors: Vec<String>,
-    #[serde(rename = "totalContributions")]
-    total_contributions: i64,
     weeks: Vec<GitHubWeek>,
 }
 
-#[allow(dead_code)]
 #[derive(Deserialize, Debug)]
 struct GitHubWeek {
     #[serde(rename = "contributionDays")]
     contribution_days: Vec<GitHubContributionDay>,
-    #[serde(rename = "firstDay")]
-    first_day: String,
 }
 
-#[allow(dead_code)]
 #[derive(Deserialize, Debug, Clone)]
 struct GitHubContributionDay {
-    color: String,
     #[serde(rename = "contributionCount")]
     contribution_count: i64,
     date: String,
-    weekday: i64,
 }
 
 impl Event for GitHubContributionDay {
@@ -113,72 +160,326 @@ impl Event for GitHubContributionDay {
     }
 }
 
-struct GitHubClient {
-    client: Client,
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct GitHubBreakdownCollection {
+    total_commit_contributions: i64,
+    total_pull_request_contributions: i64,
+    total_issue_contributions: i64,
+    total_pull_request_review_contributions: i64,
+    restricted_contributions_count: i64,
+    commit_contributions_by_repository: Vec<GitHubRepositoryContributions>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct GitHubWeekCollection {
+    total_commit_contributions: i64,
+    total_pull_request_contributions: i64,
+    total_issue_contributions: i64,
+    total_pull_request_review_contributions: i64,
+}
+
+impl From<GitHubWeekCollection> for KindCounts {
+    fn from(collection: GitHubWeekCollection) -> Self {
+        Self {
+            commits: collection.total_commit_contributions,
+            pull_requests: collection.total_pull_request_contributions,
+            issues: collection.total_issue_contributions,
+            reviews: collection.total_pull_request_review_contributions,
+            comments: 0,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubRepositoryContributions {
+    repository: GitHubRepository,
+    contributions: GitHubCount,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubRepository {
+    #[serde(rename = "nameWithOwner")]
+    name_with_owner: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubCount {
+    #[serde(rename = "totalCount")]
+    total_count: i64,
+}
+
+/// Splits the range into the ranges of the collections it is fetched in. Longer ranges are
+/// fetched a year at a time, and every range ends just before midnight so no day is split
+/// between two collections.
+fn collection_ranges(
+    start_date: OffsetDateTime,
+    end_date: OffsetDateTime,
+) -> Vec<(OffsetDateTime, OffsetDateTime)> {
+    let mut ranges = Vec::new();
+    let mut from = start_date;
+    while from < end_date {
+        let Some(next) = from.checked_add(MAX_COLLECTION_SPAN) else {
+            ranges.push((from, end_date));
+            break;
+        };
+        let next = next.replace_time(Time::MIDNIGHT);
+        ranges.push((from, (next - Duration::SECOND).min(end_date)));
+        from = next;
+    }
+    ranges
+}
+
+/// Splits the range into the weeks of the graph, which starts on a Sunday. Every week ends
+/// just before the midnight the next one starts at.
+fn week_ranges(
+    start_date: OffsetDateTime,
+    end_date: OffsetDateTime,
+) -> Vec<(OffsetDateTime, OffsetDateTime)> {
+    let mut ranges = Vec::new();
+    let mut from = start_date;
+    while from < end_date {
+        let Some(next) = from.checked_add(Duration::WEEK) else {
+            ranges.push((from, end_date));
+            break;
+        };
+        ranges.push((from, (next - Duration::SECOND).min(end_date)));
+        from = next;
+    }
+    ranges
+}
+
+fn format_date(date: OffsetDateTime) -> Result<String, ProviderError> {
+    date.format(&Rfc3339)
+        .map_err(|e| ProviderError::DateError(format!("Failed to format date: {}", e)))
+}
+
+pub struct GitHubClient {
+    client: HttpClient,
     config: GitHubConfig,
 }
 
 impl GitHubClient {
     fn new(config: GitHubConfig) -> Self {
         Self {
-            client: Client::new(),
+            client: HttpClient::new(config.name()),
             config,
         }
     }
 
-    async fn fetch_events(
+    /// Queries the user's contributions collection between `from` and `to`.
+    async fn query<T: DeserializeOwned>(
         &self,
-        _start_date: OffsetDateTime,
-        _end_date: OffsetDateTime,
-    ) -> Result<Vec<GitHubContributionDay>, ProviderError> {
+        query: &str,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> Result<T, ProviderError> {
         let variables = serde_json::json!({
             "username": self.config.username,
+            "from": format_date(from)?,
+            "to": format_date(to)?,
+            "repositories": BREAKDOWN_REPOSITORIES,
         });
+        let user: GitHubUser<T> = self.post(query, variables).await?;
+        Ok(user.contributions_collection)
+    }
+
+    /// Queries what the contributions of each of the weeks consist of. A collection only has
+    /// totals, so every week gets a collection of its own.
+    async fn query_weeks(
+        &self,
+        weeks: &[(OffsetDateTime, OffsetDateTime)],
+    ) -> Result<Vec<KindCounts>, ProviderError> {
+        let mut parameters = "$username: String!".to_string();
+        let mut collections = String::new();
+        let mut variables = serde_json::Map::new();
+        variables.insert("username".to_string(), self.config.username.clone().into());
+        for (week, (from, to)) in weeks.iter().enumerate() {
+            parameters.push_str(&format!(", $from{0}: DateTime!, $to{0}: DateTime!", week));
+            collections.push_str(&format!(
+                "week{0}: contributionsCollection(from: $from{0}, to: $to{0}) {{ {1} }}\n",
+                week, GITHUB_WEEK_FIELDS
+            ));
+            variables.insert(format!("from{}", week), format_date(*from)?.into());
+            variables.insert(format!("to{}", week), format_date(*to)?.into());
+        }
+        let query = format!(
+            "query({}) {{\n    user(login: $username) {{\n{}    }}\n}}",
+            parameters, collections
+        );
 
+        let mut user: HashMap<String, GitHubWeekCollection> =
+            self.post(&query, variables.into()).await?;
+        (0..weeks.len())
+            .map(|week| {
+                user.remove(&format!("week{}", week))
+                    .map(KindCounts::from)
+                    .ok_or_else(|| {
+                        ProviderError::ApiError(format!("No collection of week {} returned", week))
+                    })
+            })
+            .collect()
+    }
+
+    /// Sends a GraphQL query about the user and returns what it returned about them.
+    async fn post<U: DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<U, ProviderError> {
         let body = serde_json::json!({
-            "query": GITHUB_CONTRIBUTIONS_QUERY,
+            "query": query,
             "variables": variables,
         });
 
-        let response = self
+        let request = self
             .client
-            .post("https://api.github.com/graphql")
-            .header("Authorization", format!("Bearer {}", self.config.token))
+            .post(self.config.graphql_endpoint())
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.config.token.expose()),
+            )
             .header("User-Agent", "All Contributions CLI")
-            .json(&body)
-            .send()
-            .await?
-            .error_for_status()?;
+            .json(&body);
+        let response = self.client.send(request).await?;
+        let response = self.client.error_for_status(response).await?;
 
-        let github_response: GitHubResponse = response.json().await?;
-        let events = github_response
+        let url = response.url().clone();
+        let github_response: GitHubResponse<U> = self.client.json(response).await?;
+        // GraphQL reports errors with a successful status, in the body.
+        if let Some(errors) = github_response.errors.filter(|errors| !errors.is_empty()) {
+            let has_kind = |kind: &str| errors.iter().any(|e| e.kind.as_deref() == Some(kind));
+            let not_found = has_kind("NOT_FOUND");
+            let rate_limited = has_kind("RATE_LIMITED");
+            let messages: Vec<String> = errors.into_iter().map(|error| error.message).collect();
+            let context = self.client.context(&url, None, Some(&messages.join("; ")));
+            return Err(if rate_limited {
+                ProviderError::RateLimitError(context)
+            } else if not_found {
+                ProviderError::NotFoundError(context)
+            } else {
+                ProviderError::ApiError(context.to_string())
+            });
+        }
+        github_response
             .data
-            .user
-            .contributions_collection
-            .contribution_calendar
-            .weeks
-            .into_iter()
-            .flat_map(|week| week.contribution_days)
-            .collect();
-        Ok(events)
+            .and_then(|data| data.user)
+            .ok_or_else(|| {
+                let message = format!("User {} not found", self.config.username);
+                ProviderError::NotFoundError(self.client.context(&url, None, Some(&message)))
+            })
     }
 }
 
-pub async fn get_github_contributions(
-    start_date: OffsetDateTime,
-    end_date: OffsetDateTime,
-) -> Result<ContributionCollection, ProviderError> {
-    let config = GitHubConfig::from_env()?;
-    let client = GitHubClient::new(config);
-    let events = client.fetch_events(start_date, end_date).await?;
-
-    let processor = ContributionProcessor::new(start_date, end_date);
-    let calendar = processor.initialize_contribution_calendar();
-    let (contributions, max_contributions) = processor.process_events(events, calendar)?;
-
-    Ok(ContributionCollection {
-        provider: "GitHub".to_string(),
-        contributions,
-        max_contributions,
-    })
+impl Provider for GitHubClient {
+    fn name(&self) -> &str {
+        self.config.name()
+    }
+
+    async fn from_env(prefix: &str) -> Result<Self, ProviderError> {
+        Ok(Self::new(GitHubConfig::from_env(prefix).await?))
+    }
+
+    fn fetch_events(
+        &self,
+        start_date: OffsetDateTime,
+        end_date: OffsetDateTime,
+    ) -> BoxFuture<'_, Result<Events, ProviderError>> {
+        Box::pin(async move {
+            let mut events = Vec::new();
+            for (from, to) in collection_ranges(start_date, end_date) {
+                let collection: GitHubContributionsCollection =
+                    self.query(GITHUB_CONTRIBUTIONS_QUERY, from, to).await?;
+                events.extend(
+                    collection
+                        .contribution_calendar
+                        .weeks
+                        .into_iter()
+                        .flat_map(|week| week.contribution_days),
+                );
+            }
+            Ok(into_events(events))
+        })
+    }
+
+    fn fetch_breakdown(
+        &self,
+        start_date: OffsetDateTime,
+        end_date: OffsetDateTime,
+    ) -> BoxFuture<'_, Result<Option<ContributionBreakdown>, ProviderError>> {
+        Box::pin(async move {
+            let mut breakdown = ContributionBreakdown::default();
+            for (from, to) in collection_ranges(start_date, end_date) {
+                let collection: GitHubBreakdownCollection =
+                    self.query(GITHUB_BREAKDOWN_QUERY, from, to).await?;
+                breakdown.kinds.commits += collection.total_commit_contributions;
+                breakdown.kinds.pull_requests += collection.total_pull_request_contributions;
+                breakdown.kinds.issues += collection.total_issue_contributions;
+                breakdown.kinds.reviews += collection.total_pull_request_review_contributions;
+                breakdown.restricted += collection.restricted_contributions_count;
+                for contributions in collection.commit_contributions_by_repository {
+                    breakdown.add_repository_commits(
+                        contributions.repository.name_with_owner,
+                        contributions.contributions.total_count,
+                    );
+                }
+            }
+            breakdown
+                .repositories
+                .sort_by_key(|(_, commits)| std::cmp::Reverse(*commits));
+            breakdown.repositories.truncate(BREAKDOWN_REPOSITORIES);
+
+            for weeks in week_ranges(start_date, end_date).chunks(BREAKDOWN_WEEKS_PER_QUERY) {
+                breakdown.weeks.extend(self.query_weeks(weeks).await?);
+            }
+            Ok(Some(breakdown))
+        })
+    }
+
+    fn quota(&self) -> Option<Quota> {
+        self.client.quota()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Date;
+    use time::format_description::well_known::Iso8601;
+
+    fn midnight(date: &str) -> OffsetDateTime {
+        Date::parse(date, &Iso8601::DATE)
+            .unwrap()
+            .midnight()
+            .assume_utc()
+    }
+
+    #[test]
+    fn collection_ranges_fit_short_ranges_into_one() {
+        let (start, end) = (midnight("2025-01-05"), midnight("2025-03-01"));
+        assert_eq!(collection_ranges(start, end), vec![(start, end)]);
+    }
+
+    #[test]
+    fn collection_ranges_split_years_without_splitting_days() {
+        let (start, end) = (midnight("2023-12-31"), midnight("2026-01-04"));
+        let second = midnight("2024-12-30");
+        let third = midnight("2025-12-30");
+        assert_eq!(
+            collection_ranges(start, end),
+            vec![
+                (start, second - Duration::SECOND),
+                (second, third - Duration::SECOND),
+                (third, end),
+            ]
+        );
+    }
+
+    #[test]
+    fn collection_ranges_end_with_the_last_date() {
+        let (start, end) = (midnight("9999-06-06"), midnight("9999-12-31"));
+        assert_eq!(collection_ranges(start, end), vec![(start, end)]);
+        assert!(collection_ranges(end, end).is_empty());
+    }
 }
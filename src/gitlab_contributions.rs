@@ -1,32 +1,153 @@
-use crate::processor::{ContributionProcessor, Event};
-use crate::{ContributionCollection, ProviderError};
-use reqwest::Client;
+use crate::ProviderError;
+use crate::http::{HttpClient, Quota};
+use crate::processor::Event;
+use crate::provider::{
+    BoxFuture, Events, GenericConfig, Provider, ProviderConfig, into_events, weight_var,
+};
+use reqwest::Url;
+use reqwest::header::HeaderMap;
 use serde::Deserialize;
 use time::OffsetDateTime;
 use time::format_description::well_known::Rfc3339;
 
+const PAGE_SIZE: usize = 100;
+
+/// Settings of the GitLab provider. Weights of actions on issues and merge requests can also
+/// be set for either alone, such as `WEIGHT_MERGE_REQUEST_OPENED` next to `WEIGHT_OPENED`.
+pub const SETTINGS: &[&str] = &[
+    "SERVER",
+    "USERNAME",
+    "WEIGHT_PUSHED",
+    "WEIGHT_COMMIT",
+    "WEIGHT_OPENED",
+    "WEIGHT_ISSUE_OPENED",
+    "WEIGHT_MERGE_REQUEST_OPENED",
+    "WEIGHT_CLOSED",
+    "WEIGHT_ISSUE_CLOSED",
+    "WEIGHT_MERGE_REQUEST_CLOSED",
+    "WEIGHT_REOPENED",
+    "WEIGHT_ISSUE_REOPENED",
+    "WEIGHT_MERGE_REQUEST_REOPENED",
+    "WEIGHT_MERGED",
+    "WEIGHT_ISSUE_MERGED",
+    "WEIGHT_MERGE_REQUEST_MERGED",
+    "WEIGHT_APPROVED",
+    "WEIGHT_ISSUE_APPROVED",
+    "WEIGHT_MERGE_REQUEST_APPROVED",
+    "WEIGHT_COMMENTED",
+    "WEIGHT_OTHER",
+];
+
+/// How much an action counts on issues and on merge requests. `WEIGHT_<ACTION>` sets both,
+/// `WEIGHT_ISSUE_<ACTION>` and `WEIGHT_MERGE_REQUEST_<ACTION>` override either.
+#[derive(Debug)]
+struct IssuableWeights {
+    issue: i64,
+    merge_request: i64,
+}
+
+impl IssuableWeights {
+    fn from_env(prefix: &str, action: &str, default: i64) -> Result<Self, ProviderError> {
+        let both = weight_var(prefix, &format!("WEIGHT_{}", action), default)?;
+        Ok(Self {
+            issue: weight_var(prefix, &format!("WEIGHT_ISSUE_{}", action), both)?,
+            merge_request: weight_var(prefix, &format!("WEIGHT_MERGE_REQUEST_{}", action), both)?,
+        })
+    }
+
+    /// The weight for an event on a `target_type`, which is `None` for events on other targets
+    /// such as milestones or projects.
+    fn get(&self, target_type: Option<&str>) -> Option<i64> {
+        match target_type? {
+            "Issue" | "WorkItem" => Some(self.issue),
+            "MergeRequest" => Some(self.merge_request),
+            _ => None,
+        }
+    }
+}
+
+/// How much each kind of GitLab event counts towards the graph. The defaults count the same
+/// events as GitLab's own contribution calendar.
+#[derive(Debug)]
+struct GitLabWeights {
+    /// Per push, regardless of the number of commits in it.
+    pushed: i64,
+    /// Per commit in a push.
+    commit: i64,
+    opened: IssuableWeights,
+    closed: IssuableWeights,
+    reopened: IssuableWeights,
+    merged: IssuableWeights,
+    approved: IssuableWeights,
+    commented: i64,
+    /// Every other action, such as joining a project or deleting a branch.
+    other: i64,
+}
+
+impl GitLabWeights {
+    fn from_env(prefix: &str) -> Result<Self, ProviderError> {
+        Ok(Self {
+            pushed: weight_var(prefix, "WEIGHT_PUSHED", 1)?,
+            commit: weight_var(prefix, "WEIGHT_COMMIT", 0)?,
+            opened: IssuableWeights::from_env(prefix, "OPENED", 1)?,
+            closed: IssuableWeights::from_env(prefix, "CLOSED", 1)?,
+            reopened: IssuableWeights::from_env(prefix, "REOPENED", 0)?,
+            merged: IssuableWeights::from_env(prefix, "MERGED", 1)?,
+            approved: IssuableWeights::from_env(prefix, "APPROVED", 1)?,
+            commented: weight_var(prefix, "WEIGHT_COMMENTED", 1)?,
+            other: weight_var(prefix, "WEIGHT_OTHER", 0)?,
+        })
+    }
+
+    fn weight(&self, event: &GitLabEvent) -> i64 {
+        let issuable = match event.action_name.as_str() {
+            "pushed to" | "pushed new" => {
+                let commits = event.push_data.as_ref().map_or(0, |push| push.commit_count);
+                return self.pushed + self.commit * commits;
+            }
+            "commented on" => return self.commented,
+            "opened" => &self.opened,
+            "closed" => &self.closed,
+            "reopened" => &self.reopened,
+            "merged" | "accepted" => &self.merged,
+            "approved" => &self.approved,
+            _ => return self.other,
+        };
+        // Opening, closing, merging and approving only count for issues and merge requests,
+        // not for milestones or projects.
+        issuable
+            .get(event.target_type.as_deref())
+            .unwrap_or(self.other)
+    }
+}
+
 #[derive(Debug)]
 struct GitLabConfig {
-    server: String,
-    username: String,
-    token: String,
+    generic: GenericConfig,
+    weights: GitLabWeights,
 }
 
 impl GitLabConfig {
-    fn from_env() -> Result<Self, ProviderError> {
+    async fn from_env(prefix: &str) -> Result<Self, ProviderError> {
         Ok(Self {
-            server: dotenv::var("GITLAB_SERVER")
-                .map_err(|_| ProviderError::ConfigError("GITLAB_SERVER must be set".into()))?
-                .trim_end_matches('/')
-                .to_string(),
-            username: dotenv::var("GITLAB_USERNAME")
-                .map_err(|_| ProviderError::ConfigError("GITLAB_USERNAME must be set".into()))?,
-            token: dotenv::var("GITLAB_TOKEN")
-                .map_err(|_| ProviderError::ConfigError("GITLAB_TOKEN must be set".into()))?,
+            generic: GenericConfig::from_env(prefix).await?,
+            weights: GitLabWeights::from_env(prefix)?,
         })
     }
 }
 
+impl ProviderConfig for GitLabConfig {
+    fn server(&self) -> &str {
+        self.generic.server()
+    }
+    fn username(&self) -> &str {
+        self.generic.username()
+    }
+    fn token(&self) -> &str {
+        self.generic.token()
+    }
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Deserialize)]
 struct GitLabEvent {
@@ -39,77 +160,273 @@ struct GitLabEvent {
     author_id: i64,
     target_title: Option<String>,
     created_at: String,
+    push_data: Option<GitLabPushData>,
 }
 
-impl Event for GitLabEvent {
+#[derive(Debug, Deserialize)]
+struct GitLabPushData {
+    commit_count: i64,
+}
+
+#[derive(Debug)]
+struct GitLabActivity {
+    timestamp: String,
+    weight: i64,
+}
+
+impl Event for GitLabActivity {
     fn timestamp(&self) -> Result<OffsetDateTime, ProviderError> {
-        OffsetDateTime::parse(&self.created_at, &Rfc3339)
+        OffsetDateTime::parse(&self.timestamp, &Rfc3339)
             .map_err(|e| ProviderError::DateError(format!("Failed to parse date: {}", e)))
     }
 
     fn contributions(&self) -> i64 {
-        1
+        self.weight
+    }
+}
+
+/// Finds the next page through the `Link` header, or else the `X-Next-Page` header.
+fn next_page(current: &Url, headers: &HeaderMap) -> Option<Url> {
+    let link = headers
+        .get("link")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|links| links.split(',').find(|link| link.contains("rel=\"next\"")))
+        .and_then(|link| {
+            let start = link.find('<')? + 1;
+            let end = link.find('>')?;
+            Url::parse(&link[start..end]).ok()
+        });
+    if link.is_some() {
+        return link;
+    }
+
+    // The header is present but empty on the last page.
+    let page = headers.get("x-next-page")?.to_str().ok()?.trim();
+    if page.is_empty() {
+        return None;
     }
+    let query: Vec<(String, String)> = current
+        .query_pairs()
+        .filter(|(key, _)| key != "page")
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    let mut url = current.clone();
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(query)
+        .append_pair("page", page);
+    Some(url)
 }
 
-struct GitLabClient {
-    client: Client,
+pub struct GitLabClient {
+    client: HttpClient,
     config: GitLabConfig,
 }
 
 impl GitLabClient {
     fn new(config: GitLabConfig) -> Self {
         Self {
-            client: Client::new(),
+            client: HttpClient::new("GitLab"),
             config,
         }
     }
+}
+
+impl Provider for GitLabClient {
+    fn name(&self) -> &str {
+        "GitLab"
+    }
+
+    async fn from_env(prefix: &str) -> Result<Self, ProviderError> {
+        Ok(Self::new(GitLabConfig::from_env(prefix).await?))
+    }
 
-    async fn fetch_events(
+    fn fetch_events(
         &self,
         start_date: OffsetDateTime,
         end_date: OffsetDateTime,
-    ) -> Result<Vec<GitLabEvent>, ProviderError> {
-        let endpoint = format!(
-            "{}/api/v4/users/{}/events?after={}-{}-{}&before={}-{}-{}",
-            self.config.server,
-            self.config.username,
-            start_date.year(),
-            start_date.month(),
-            start_date.day(),
-            end_date.year(),
-            end_date.month(),
-            end_date.day()
-        );
+    ) -> BoxFuture<'_, Result<Events, ProviderError>> {
+        Box::pin(async move {
+            // Both bounds of the events API are exclusive.
+            let after = start_date
+                .date()
+                .previous_day()
+                .unwrap_or(start_date.date());
+            let before = end_date.date().next_day().unwrap_or(end_date.date());
+            let endpoint = format!(
+                "{}/api/v4/users/{}/events",
+                self.config.server(),
+                self.config.username()
+            );
+            let mut url = Some(
+                Url::parse_with_params(
+                    &endpoint,
+                    &[
+                        ("after", after.to_string()),
+                        ("before", before.to_string()),
+                        ("per_page", PAGE_SIZE.to_string()),
+                    ],
+                )
+                .map_err(|e| ProviderError::ConfigError(format!("Invalid GitLab server: {}", e)))?,
+            );
 
-        let response = self
-            .client
-            .get(endpoint)
-            .header("PRIVATE-TOKEN", &self.config.token)
-            .send()
-            .await?
-            .error_for_status()?;
+            let mut events = Vec::new();
+            while let Some(current) = url.take() {
+                let request = self
+                    .client
+                    .get(current.clone())
+                    .header("PRIVATE-TOKEN", self.config.token());
+                let response = self.client.send(request).await?;
+                let response = self.client.error_for_status(response).await?;
+                url = next_page(&current, response.headers());
+                let page: Vec<GitLabEvent> = self.client.json(response).await?;
+                events.extend(
+                    page.into_iter()
+                        .map(|event| GitLabActivity {
+                            weight: self.config.weights.weight(&event),
+                            timestamp: event.created_at,
+                        })
+                        .filter(|activity| activity.weight != 0),
+                );
+            }
 
-        let events: Vec<GitLabEvent> = response.json().await?;
-        Ok(events)
+            Ok(into_events(events))
+        })
+    }
+
+    fn quota(&self) -> Option<Quota> {
+        self.client.quota()
     }
 }
 
-pub async fn get_gitlab_contributions(
-    start_date: OffsetDateTime,
-    end_date: OffsetDateTime,
-) -> Result<ContributionCollection, ProviderError> {
-    let config = GitLabConfig::from_env()?;
-    let client = GitLabClient::new(config);
-    let events = client.fetch_events(start_date, end_date).await?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    fn current() -> Url {
+        Url::parse("https://gitlab.com/api/v4/users/me/events?per_page=100&page=2").unwrap()
+    }
+
+    fn event(action: &str, target_type: Option<&str>, commits: Option<i64>) -> GitLabEvent {
+        serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "project_id": 1,
+            "action_name": action,
+            "target_type": target_type,
+            "author_id": 1,
+            "created_at": "2025-03-03T12:00:00Z",
+            "push_data": commits.map(|commits| serde_json::json!({ "commit_count": commits })),
+        }))
+        .unwrap()
+    }
+
+    fn weights() -> GitLabWeights {
+        let issuable = |issue, merge_request| IssuableWeights {
+            issue,
+            merge_request,
+        };
+        GitLabWeights {
+            pushed: 1,
+            commit: 2,
+            opened: issuable(3, 4),
+            closed: issuable(5, 6),
+            reopened: issuable(0, 0),
+            merged: issuable(7, 8),
+            approved: issuable(9, 10),
+            commented: 11,
+            other: 12,
+        }
+    }
+
+    #[test]
+    fn weight_counts_pushes_and_their_commits() {
+        let weights = weights();
+        assert_eq!(weights.weight(&event("pushed to", None, Some(3))), 7);
+        assert_eq!(weights.weight(&event("pushed new", None, None)), 1);
+    }
 
-    let processor = ContributionProcessor::new(start_date, end_date);
-    let calendar = processor.initialize_contribution_calendar();
-    let (contributions, max_contributions) = processor.process_events(events, calendar)?;
+    #[test]
+    fn weight_tells_issues_and_merge_requests_apart() {
+        let weights = weights();
+        assert_eq!(weights.weight(&event("opened", Some("Issue"), None)), 3);
+        assert_eq!(weights.weight(&event("opened", Some("WorkItem"), None)), 3);
+        assert_eq!(
+            weights.weight(&event("opened", Some("MergeRequest"), None)),
+            4
+        );
+        assert_eq!(
+            weights.weight(&event("closed", Some("MergeRequest"), None)),
+            6
+        );
+        assert_eq!(
+            weights.weight(&event("accepted", Some("MergeRequest"), None)),
+            8
+        );
+        assert_eq!(
+            weights.weight(&event("approved", Some("MergeRequest"), None)),
+            10
+        );
+        assert_eq!(weights.weight(&event("reopened", Some("Issue"), None)), 0);
+    }
 
-    Ok(ContributionCollection {
-        provider: "GitLab".to_string(),
-        contributions,
-        max_contributions,
-    })
+    #[test]
+    fn weight_counts_other_targets_as_other() {
+        let weights = weights();
+        assert_eq!(
+            weights.weight(&event("opened", Some("Milestone"), None)),
+            12
+        );
+        assert_eq!(weights.weight(&event("closed", None, None)), 12);
+        assert_eq!(weights.weight(&event("joined", None, None)), 12);
+        assert_eq!(
+            weights.weight(&event("commented on", Some("Note"), None)),
+            11
+        );
+    }
+
+    #[test]
+    fn next_page_follows_the_link_header() {
+        let headers = headers(&[(
+            "link",
+            "<https://gitlab.com/api/v4/users/me/events?page=1>; rel=\"prev\", \
+             <https://gitlab.com/api/v4/users/me/events?page=3>; rel=\"next\"",
+        )]);
+        assert_eq!(
+            next_page(&current(), &headers).unwrap().as_str(),
+            "https://gitlab.com/api/v4/users/me/events?page=3"
+        );
+    }
+
+    #[test]
+    fn next_page_falls_back_to_x_next_page() {
+        let headers = headers(&[
+            (
+                "link",
+                "<https://gitlab.com/api/v4/users/me/events?page=1>; rel=\"first\"",
+            ),
+            ("x-next-page", "3"),
+        ]);
+        assert_eq!(
+            next_page(&current(), &headers).unwrap().as_str(),
+            "https://gitlab.com/api/v4/users/me/events?per_page=100&page=3"
+        );
+    }
+
+    #[test]
+    fn next_page_ends_at_an_empty_x_next_page() {
+        assert_eq!(
+            next_page(&current(), &headers(&[("x-next-page", "")])),
+            None
+        );
+        assert_eq!(next_page(&current(), &HeaderMap::new()), None);
+    }
 }
@@ -0,0 +1,246 @@
+use crate::{
+    ProviderError,
+    http::{HttpClient, Quota},
+    processor::Event,
+    provider::{BoxFuture, Events, Provider, describe, into_events, required_var, secret_var, var},
+    secret::Secret,
+};
+use serde_json::Value;
+use time::format_description::OwnedFormatItem;
+use time::format_description::well_known::{Iso8601, Rfc3339};
+use time::{Date, OffsetDateTime, PrimitiveDateTime};
+
+/// How the timestamp field of each entry is encoded.
+#[derive(Debug)]
+enum JsonDateFormat {
+    Rfc3339,
+    /// A plain `YYYY-MM-DD` date.
+    Date,
+    /// Seconds since the Unix epoch.
+    Unix,
+    /// Milliseconds since the Unix epoch.
+    UnixMillis,
+    /// A `time` format description such as `[day]/[month]/[year]`.
+    Custom(OwnedFormatItem),
+}
+
+impl JsonDateFormat {
+    /// Parses the `<PREFIX>_DATE_FORMAT` setting.
+    fn parse(prefix: &str, format: &str) -> Result<Self, ProviderError> {
+        match format {
+            "rfc3339" => Ok(Self::Rfc3339),
+            "date" => Ok(Self::Date),
+            "unix" => Ok(Self::Unix),
+            "unix_ms" => Ok(Self::UnixMillis),
+            custom => time::format_description::parse_owned::<2>(custom)
+                .map(Self::Custom)
+                .map_err(|e| {
+                    ProviderError::ConfigError(format!(
+                        "{} is not a valid format: {}",
+                        describe(prefix, "DATE_FORMAT"),
+                        e
+                    ))
+                }),
+        }
+    }
+
+    fn timestamp(&self, value: &Value) -> Result<OffsetDateTime, ProviderError> {
+        let invalid = || ProviderError::DateError(format!("Failed to parse date: {}", value));
+        let text = || value.as_str().ok_or_else(invalid);
+        let number = || {
+            value
+                .as_i64()
+                .or_else(|| value.as_str().and_then(|text| text.parse().ok()))
+                .ok_or_else(invalid)
+        };
+
+        match self {
+            Self::Rfc3339 => OffsetDateTime::parse(text()?, &Rfc3339).map_err(|_| invalid()),
+            Self::Date => Date::parse(text()?, &Iso8601::DATE)
+                .map(|date| date.midnight().assume_utc())
+                .map_err(|_| invalid()),
+            Self::Unix => OffsetDateTime::from_unix_timestamp(number()?).map_err(|_| invalid()),
+            Self::UnixMillis => {
+                OffsetDateTime::from_unix_timestamp_nanos(number()? as i128 * 1_000_000)
+                    .map_err(|_| invalid())
+            }
+            Self::Custom(format) => {
+                let text = text()?;
+                OffsetDateTime::parse(text, format)
+                    .or_else(|_| {
+                        PrimitiveDateTime::parse(text, format).map(|date| date.assume_utc())
+                    })
+                    .or_else(|_| Date::parse(text, format).map(|date| date.midnight().assume_utc()))
+                    .map_err(|_| invalid())
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct JsonHeatmapConfig {
+    name: String,
+    url: String,
+    auth_header: Option<(String, Secret)>,
+    items_path: String,
+    date_field: String,
+    count_field: String,
+    date_format: JsonDateFormat,
+}
+
+impl JsonHeatmapConfig {
+    async fn from_env(prefix: &str) -> Result<Self, ProviderError> {
+        let auth_header = match secret_var(prefix, "AUTH_HEADER").await? {
+            Some(header) => {
+                let (name, value) = header.expose().split_once(':').ok_or_else(|| {
+                    ProviderError::ConfigError(format!(
+                        "{} must look like `Name: value`",
+                        describe(prefix, "AUTH_HEADER")
+                    ))
+                })?;
+                Some((
+                    name.trim().to_string(),
+                    Secret::new(value.trim().to_string()),
+                ))
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            name: var(prefix, "NAME").unwrap_or_else(|| "JSON".to_string()),
+            url: required_var(prefix, "URL")?,
+            auth_header,
+            items_path: var(prefix, "ITEMS_PATH").unwrap_or_default(),
+            date_field: var(prefix, "DATE_FIELD").unwrap_or_else(|| "date".into()),
+            count_field: var(prefix, "COUNT_FIELD").unwrap_or_else(|| "count".into()),
+            date_format: JsonDateFormat::parse(
+                prefix,
+                &var(prefix, "DATE_FORMAT").unwrap_or_else(|| "rfc3339".into()),
+            )?,
+        })
+    }
+}
+
+/// Looks up a dot separated path such as `data.days` or `stats.0.when` in `value`.
+/// An empty path refers to `value` itself.
+fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    if path.is_empty() {
+        return Some(value);
+    }
+    value.pointer(&format!("/{}", path.replace('.', "/")))
+}
+
+#[derive(Debug)]
+struct JsonHeatmapEntry {
+    timestamp: OffsetDateTime,
+    count: i64,
+}
+
+impl Event for JsonHeatmapEntry {
+    fn timestamp(&self) -> Result<OffsetDateTime, ProviderError> {
+        Ok(self.timestamp)
+    }
+    fn contributions(&self) -> i64 {
+        self.count
+    }
+}
+
+pub struct JsonHeatmapClient {
+    client: HttpClient,
+    config: JsonHeatmapConfig,
+}
+
+impl JsonHeatmapClient {
+    fn new(config: JsonHeatmapConfig) -> Self {
+        Self {
+            client: HttpClient::new(&config.name),
+            config,
+        }
+    }
+
+    async fn fetch_document(&self) -> Result<Value, ProviderError> {
+        if let Some(path) = self.config.url.strip_prefix("file://") {
+            let contents = tokio::fs::read_to_string(path).await.map_err(|source| {
+                ProviderError::ReadError {
+                    path: path.to_string(),
+                    source,
+                }
+            })?;
+            return serde_json::from_str(&contents).map_err(|source| ProviderError::SyntaxError {
+                path: path.to_string(),
+                source,
+            });
+        }
+
+        let mut request = self
+            .client
+            .get(&self.config.url)
+            .header("accept", "application/json");
+        if let Some((name, value)) = &self.config.auth_header {
+            request = request.header(name, value.expose());
+        }
+        let response = self.client.send(request).await?;
+        let response = self.client.error_for_status(response).await?;
+        self.client.json(response).await
+    }
+}
+
+impl Provider for JsonHeatmapClient {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    async fn from_env(prefix: &str) -> Result<Self, ProviderError> {
+        Ok(Self::new(JsonHeatmapConfig::from_env(prefix).await?))
+    }
+
+    fn fetch_events(
+        &self,
+        _start_date: OffsetDateTime,
+        _end_date: OffsetDateTime,
+    ) -> BoxFuture<'_, Result<Events, ProviderError>> {
+        Box::pin(async move {
+            let document = self.fetch_document().await?;
+            let items = lookup(&document, &self.config.items_path)
+                .and_then(Value::as_array)
+                .ok_or_else(|| {
+                    ProviderError::ApiError(format!(
+                        "No list of entries found at \"{}\"",
+                        self.config.items_path
+                    ))
+                })?;
+
+            let events = items
+                .iter()
+                .map(|item| {
+                    let date = lookup(item, &self.config.date_field).ok_or_else(|| {
+                        ProviderError::ApiError(format!(
+                            "Entry has no \"{}\" field: {}",
+                            self.config.date_field, item
+                        ))
+                    })?;
+                    // Entries without a count field are single contributions.
+                    let count = match lookup(item, &self.config.count_field) {
+                        Some(count) => count.as_i64().ok_or_else(|| {
+                            ProviderError::ApiError(format!(
+                                "\"{}\" is not a whole number: {}",
+                                self.config.count_field, count
+                            ))
+                        })?,
+                        None => 1,
+                    };
+
+                    Ok(JsonHeatmapEntry {
+                        timestamp: self.config.date_format.timestamp(date)?,
+                        count,
+                    })
+                })
+                .collect::<Result<Vec<_>, ProviderError>>()?;
+            Ok(into_events(events))
+        })
+    }
+
+    fn quota(&self) -> Option<Quota> {
+        self.client.quota()
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{
+    ProviderError,
+    http::{HttpClient, Quota},
+    processor::Event,
+    provider::{BoxFuture, Events, Provider, into_events, required_var, var},
+};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+/// Every status of a merge proposal, so merged and otherwise finished proposals are included.
+const MERGE_PROPOSAL_STATUSES: &[&str] = &[
+    "Work in progress",
+    "Needs review",
+    "Approved",
+    "Rejected",
+    "Merged",
+    "Code failed to merge",
+    "Queued",
+    "Superseded",
+];
+
+#[derive(Debug)]
+struct LaunchpadConfig {
+    server: String,
+    username: String,
+}
+
+impl LaunchpadConfig {
+    fn from_env(prefix: &str) -> Result<Self, ProviderError> {
+        Ok(Self {
+            server: var(prefix, "SERVER")
+                .unwrap_or_else(|| "https://api.launchpad.net/devel".to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            username: required_var(prefix, "USERNAME")?
+                .trim_start_matches('~')
+                .to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct LaunchpadCollection<T> {
+    entries: Vec<T>,
+    next_collection_link: Option<String>,
+}
+
+/// A Bazaar branch or Git repository owned by the user.
+#[derive(Debug, Deserialize)]
+struct LaunchpadCodeTarget {
+    date_created: String,
+    date_last_modified: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LaunchpadMergeProposal {
+    date_created: String,
+    date_merged: Option<String>,
+}
+
+#[derive(Debug)]
+struct LaunchpadActivity {
+    date: String,
+}
+
+impl Event for LaunchpadActivity {
+    fn timestamp(&self) -> Result<OffsetDateTime, ProviderError> {
+        OffsetDateTime::parse(&self.date, &Rfc3339)
+            .map_err(|e| ProviderError::DateError(format!("Failed to parse date: {}", e)))
+    }
+    fn contributions(&self) -> i64 {
+        1
+    }
+}
+
+pub struct LaunchpadClient {
+    client: HttpClient,
+    config: LaunchpadConfig,
+}
+
+impl LaunchpadClient {
+    fn new(config: LaunchpadConfig) -> Self {
+        Self {
+            client: HttpClient::new("Launchpad"),
+            config,
+        }
+    }
+
+    fn person_link(&self) -> String {
+        format!("{}/~{}", self.config.server, self.config.username)
+    }
+
+    async fn fetch_collection<T: DeserializeOwned>(
+        &self,
+        endpoint: String,
+        query: &[(&str, String)],
+    ) -> Result<Vec<T>, ProviderError> {
+        let mut entries = Vec::new();
+        let mut request = self.client.get(endpoint).query(query);
+        loop {
+            let response = self
+                .client
+                .send(request.header("accept", "application/json"))
+                .await?;
+            let response = self.client.error_for_status(response).await?;
+
+            let collection: LaunchpadCollection<T> = self.client.json(response).await?;
+            entries.extend(collection.entries);
+            match collection.next_collection_link {
+                Some(next) => request = self.client.get(next),
+                None => break,
+            }
+        }
+        Ok(entries)
+    }
+
+    async fn fetch_code_targets(
+        &self,
+        start_date: OffsetDateTime,
+    ) -> Result<Vec<LaunchpadCodeTarget>, ProviderError> {
+        let modified_since = start_date
+            .format(&Rfc3339)
+            .map_err(|e| ProviderError::DateError(format!("Failed to format date: {}", e)))?;
+
+        let mut targets = self
+            .fetch_collection(
+                self.person_link(),
+                &[
+                    ("ws.op", "getBranches".to_string()),
+                    ("modified_since", modified_since.clone()),
+                ],
+            )
+            .await?;
+        targets.extend(
+            self.fetch_collection(
+                format!("{}/+git", self.config.server),
+                &[
+                    ("ws.op", "getRepositories".to_string()),
+                    ("target", self.person_link()),
+                    // Unlike `getBranches`, `getRepositories` calls it `modified_since_date`.
+                    ("modified_since_date", modified_since),
+                ],
+            )
+            .await?,
+        );
+        Ok(targets)
+    }
+}
+
+impl Provider for LaunchpadClient {
+    fn name(&self) -> &str {
+        "Launchpad"
+    }
+
+    async fn from_env(prefix: &str) -> Result<Self, ProviderError> {
+        Ok(Self::new(LaunchpadConfig::from_env(prefix)?))
+    }
+
+    fn fetch_events(
+        &self,
+        start_date: OffsetDateTime,
+        _end_date: OffsetDateTime,
+    ) -> BoxFuture<'_, Result<Events, ProviderError>> {
+        Box::pin(async move {
+            let mut events = Vec::new();
+
+            for target in self.fetch_code_targets(start_date).await? {
+                events.push(LaunchpadActivity {
+                    date: target.date_created,
+                });
+                events.extend(
+                    target
+                        .date_last_modified
+                        .map(|date| LaunchpadActivity { date }),
+                );
+            }
+
+            // Without a status, Launchpad only returns the proposals that are still active.
+            let mut query = vec![("ws.op", "getMergeProposals".to_string())];
+            query.extend(
+                MERGE_PROPOSAL_STATUSES
+                    .iter()
+                    .map(|status| ("status", status.to_string())),
+            );
+            let proposals: Vec<LaunchpadMergeProposal> =
+                self.fetch_collection(self.person_link(), &query).await?;
+            for proposal in proposals {
+                events.push(LaunchpadActivity {
+                    date: proposal.date_created,
+                });
+                events.extend(proposal.date_merged.map(|date| LaunchpadActivity { date }));
+            }
+
+            Ok(into_events(events))
+        })
+    }
+
+    fn quota(&self) -> Option<Quota> {
+        self.client.quota()
+    }
+}
@@ -0,0 +1,399 @@
+use crate::{
+    ProviderError,
+    processor::Event,
+    provider::{BoxFuture, Events, Provider, describe, into_events, required_var, var},
+};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+use tokio::process::Command;
+
+#[derive(Debug)]
+struct LocalConfig {
+    paths: Vec<PathBuf>,
+    /// Where the paths were set, for errors about them.
+    paths_setting: String,
+    author_emails: Vec<String>,
+    author_names: Vec<String>,
+    use_committer_date: bool,
+}
+
+impl LocalConfig {
+    fn from_env(prefix: &str) -> Result<Self, ProviderError> {
+        let paths = required_var(prefix, "REPOSITORIES")?;
+        let list = |name: &str| -> Vec<String> {
+            var(prefix, name)
+                .map(|values| {
+                    values
+                        .split(',')
+                        .map(|value| value.trim().to_lowercase())
+                        .filter(|value| !value.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        let config = Self {
+            paths: std::env::split_paths(&paths).collect(),
+            paths_setting: describe(prefix, "REPOSITORIES"),
+            author_emails: list("AUTHOR_EMAILS"),
+            author_names: list("AUTHOR_NAMES"),
+            use_committer_date: var(prefix, "USE_COMMITTER_DATE")
+                .is_some_and(|value| value == "true" || value == "1"),
+        };
+        if config.author_emails.is_empty() && config.author_names.is_empty() {
+            return Err(ProviderError::ConfigError(format!(
+                "{0}_AUTHOR_EMAILS or {0}_AUTHOR_NAMES must be set",
+                prefix
+            )));
+        }
+        Ok(config)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum RepositoryKind {
+    Git,
+    Mercurial,
+    Fossil,
+}
+
+impl RepositoryKind {
+    fn detect(path: &Path) -> Option<Self> {
+        if path.join(".git").exists()
+            || (path.join("HEAD").is_file() && path.join("objects").is_dir())
+        {
+            Some(Self::Git)
+        } else if path.join(".hg").is_dir() {
+            Some(Self::Mercurial)
+        } else if path.join(".fslckout").is_file() || path.join("_FOSSIL_").is_file() {
+            Some(Self::Fossil)
+        } else {
+            None
+        }
+    }
+
+    /// The program reading repositories of this kind.
+    fn program(self) -> &'static str {
+        match self {
+            Self::Git => "git",
+            Self::Mercurial => "hg",
+            Self::Fossil => "fossil",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct LocalCommit {
+    id: String,
+    email: Option<String>,
+    name: String,
+    date: String,
+}
+
+impl Event for LocalCommit {
+    fn timestamp(&self) -> Result<OffsetDateTime, ProviderError> {
+        OffsetDateTime::parse(&self.date, &Rfc3339)
+            .map_err(|e| ProviderError::DateError(format!("Failed to parse date: {}", e)))
+    }
+    fn contributions(&self) -> i64 {
+        1
+    }
+}
+
+/// Collects the repositories at or below `path`, without descending into repositories
+/// themselves or into hidden directories. Only `path` itself has to be readable, directories
+/// below it that are not are skipped with a warning.
+fn discover_repositories(
+    path: &Path,
+    repositories: &mut Vec<(PathBuf, RepositoryKind)>,
+) -> std::io::Result<()> {
+    if let Some(kind) = RepositoryKind::detect(path) {
+        repositories.push((path.to_path_buf(), kind));
+        return Ok(());
+    }
+
+    for entry in std::fs::read_dir(path)? {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(error) => {
+                eprintln!("Skipping an entry of {}: {}", path.display(), error);
+                continue;
+            }
+        };
+        let is_hidden = entry.file_name().to_string_lossy().starts_with('.');
+        if !is_hidden && entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+            let directory = entry.path();
+            if let Err(error) = discover_repositories(&directory, repositories) {
+                eprintln!("Skipping {}: {}", directory.display(), error);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Runs a version control command and returns its standard output.
+async fn run(command: &mut Command, repository: &Path) -> Result<String, ProviderError> {
+    let program = command
+        .as_std()
+        .get_program()
+        .to_string_lossy()
+        .into_owned();
+    let output = command.output().await.map_err(|e| {
+        ProviderError::CommandError(format!(
+            "Could not run {} in {}: {}",
+            program,
+            repository.display(),
+            e
+        ))
+    })?;
+    if !output.status.success() {
+        return Err(ProviderError::CommandError(format!(
+            "{} failed in {}: {}",
+            program,
+            repository.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Whether `program` is installed, so repositories it reads can be skipped rather than
+/// failing the repositories of every other kind.
+async fn is_installed(program: &str) -> bool {
+    !matches!(
+        Command::new(program).arg("version").output().await,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound
+    )
+}
+
+fn format_date(date: OffsetDateTime) -> Result<String, ProviderError> {
+    date.format(&Rfc3339)
+        .map_err(|e| ProviderError::DateError(format!("Failed to format date: {}", e)))
+}
+
+pub struct LocalClient {
+    config: LocalConfig,
+}
+
+impl LocalClient {
+    fn new(config: LocalConfig) -> Self {
+        Self { config }
+    }
+
+    fn is_own_commit(&self, commit: &LocalCommit) -> bool {
+        let email_matches = commit
+            .email
+            .as_ref()
+            .is_some_and(|email| self.config.author_emails.contains(&email.to_lowercase()));
+        email_matches
+            || self
+                .config
+                .author_names
+                .contains(&commit.name.to_lowercase())
+    }
+
+    async fn fetch_git_commits(
+        &self,
+        repository: &Path,
+        start_date: OffsetDateTime,
+        end_date: OffsetDateTime,
+    ) -> Result<Vec<LocalCommit>, ProviderError> {
+        let mut command = Command::new(RepositoryKind::Git.program());
+        command
+            .arg("-C")
+            .arg(repository)
+            .args(["log", "--all", "--no-merges"]);
+        // `--since` and `--until` go by committer date. Rebases, amends and cherry-picks
+        // commit long after the author date, so when counting by author date every commit
+        // is listed and the processor keeps those inside the range.
+        if self.config.use_committer_date {
+            command.args([
+                format!("--since={}", format_date(start_date)?),
+                format!("--until={}", format_date(end_date)?),
+            ]);
+        }
+        command.arg("--format=%H%x09%ae%x09%an%x09%aI%x09%cI");
+        let output = run(&mut command, repository).await?;
+
+        Ok(output
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split('\t');
+                let id = fields.next()?;
+                let email = fields.next()?;
+                let name = fields.next()?;
+                let author_date = fields.next()?;
+                let committer_date = fields.next()?;
+
+                Some(LocalCommit {
+                    id: id.to_string(),
+                    email: Some(email.to_string()),
+                    name: name.to_string(),
+                    date: if self.config.use_committer_date {
+                        committer_date
+                    } else {
+                        author_date
+                    }
+                    .to_string(),
+                })
+            })
+            .collect())
+    }
+
+    async fn fetch_mercurial_commits(
+        &self,
+        repository: &Path,
+        start_date: OffsetDateTime,
+        end_date: OffsetDateTime,
+    ) -> Result<Vec<LocalCommit>, ProviderError> {
+        let output = run(
+            Command::new(RepositoryKind::Mercurial.program())
+                .arg("--repository")
+                .arg(repository)
+                .args([
+                    "log",
+                    "--no-merges",
+                    "--date",
+                    &format!("{} to {}", start_date.date(), end_date.date()),
+                    "--template",
+                    "{node}\\t{author|email}\\t{author|person}\\t{date|rfc3339date}\\n",
+                ])
+                // Keep the output stable regardless of the user's hgrc.
+                .env("HGPLAIN", "1"),
+            repository,
+        )
+        .await?;
+
+        Ok(output
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split('\t');
+                Some(LocalCommit {
+                    id: fields.next()?.to_string(),
+                    email: Some(fields.next()?.to_string()).filter(|email| !email.is_empty()),
+                    name: fields.next()?.to_string(),
+                    date: fields.next()?.to_string(),
+                })
+            })
+            .collect())
+    }
+
+    async fn fetch_fossil_commits(
+        &self,
+        repository: &Path,
+        start_date: OffsetDateTime,
+        end_date: OffsetDateTime,
+    ) -> Result<Vec<LocalCommit>, ProviderError> {
+        // Check-ins are read straight from the timeline (the `event` table), Fossil
+        // identifies authors by user name only.
+        let query = format!(
+            "SELECT blob.uuid, coalesce(event.euser, event.user), \
+             strftime('%Y-%m-%dT%H:%M:%SZ', coalesce(event.emtime, event.mtime)) \
+             FROM event JOIN blob ON blob.rid = event.objid \
+             WHERE event.type = 'ci' \
+             AND coalesce(event.emtime, event.mtime) BETWEEN julianday({}, 'unixepoch') AND julianday({}, 'unixepoch');",
+            start_date.unix_timestamp(),
+            end_date.unix_timestamp()
+        );
+        let output = run(
+            Command::new(RepositoryKind::Fossil.program())
+                .args(["sql", "--readonly", &query])
+                .current_dir(repository),
+            repository,
+        )
+        .await?;
+
+        Ok(output
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(3, '|');
+                Some(LocalCommit {
+                    id: fields.next()?.to_string(),
+                    email: None,
+                    name: fields.next()?.to_string(),
+                    date: fields.next()?.to_string(),
+                })
+            })
+            .collect())
+    }
+}
+
+impl Provider for LocalClient {
+    fn name(&self) -> &str {
+        "Local"
+    }
+
+    async fn from_env(prefix: &str) -> Result<Self, ProviderError> {
+        Ok(Self::new(LocalConfig::from_env(prefix)?))
+    }
+
+    fn fetch_events(
+        &self,
+        start_date: OffsetDateTime,
+        end_date: OffsetDateTime,
+    ) -> BoxFuture<'_, Result<Events, ProviderError>> {
+        Box::pin(async move {
+            // Walking large directory trees blocks, keep it off the runtime's threads.
+            let paths = self.config.paths.clone();
+            let paths_setting = self.config.paths_setting.clone();
+            let repositories = tokio::task::spawn_blocking(move || {
+                let mut repositories = Vec::new();
+                for path in &paths {
+                    discover_repositories(path, &mut repositories).map_err(|e| {
+                        ProviderError::ConfigError(format!(
+                            "{} lists {}, which cannot be read: {}",
+                            paths_setting,
+                            path.display(),
+                            e
+                        ))
+                    })?;
+                }
+                Ok::<_, ProviderError>(repositories)
+            })
+            .await??;
+
+            // The same commit shows up in every clone or mirror of a repository, only
+            // count it once.
+            let mut seen = HashSet::new();
+            let mut events = Vec::new();
+            let mut installed = HashMap::new();
+            for (repository, kind) in &repositories {
+                let program = kind.program();
+                if !installed.contains_key(program) {
+                    installed.insert(program, is_installed(program).await);
+                }
+                if !installed[program] {
+                    eprintln!(
+                        "Skipping {}, {} is not installed",
+                        repository.display(),
+                        program
+                    );
+                    continue;
+                }
+
+                let commits = match kind {
+                    RepositoryKind::Git => {
+                        self.fetch_git_commits(repository, start_date, end_date)
+                            .await?
+                    }
+                    RepositoryKind::Mercurial => {
+                        self.fetch_mercurial_commits(repository, start_date, end_date)
+                            .await?
+                    }
+                    RepositoryKind::Fossil => {
+                        self.fetch_fossil_commits(repository, start_date, end_date)
+                            .await?
+                    }
+                };
+                for commit in commits {
+                    if self.is_own_commit(&commit) && seen.insert(commit.id.clone()) {
+                        events.push(commit);
+                    }
+                }
+            }
+            Ok(into_events(events))
+        })
+    }
+}
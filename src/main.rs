@@ -1,66 +1,40 @@
+use crate::cli::{Cli, Command, Format, Options};
 use crate::colors::ColorScheme;
-use crate::errors::ProviderError;
-use crate::types::{ContributionCollection, ContributionDay};
-use time::{Duration, OffsetDateTime};
+use crate::errors::{ProviderError, SettingsError};
+use crate::provider::ProviderEntry;
+use crate::types::{
+    ContributionBreakdown, ContributionCollection, ContributionDay, ProviderReport, ProviderStatus,
+};
+use clap::Parser;
+use std::process::ExitCode;
+use time::{Duration, OffsetDateTime, Time};
 
-mod codeberg_contributions;
+mod azure_devops_contributions;
+mod bitbucket_contributions;
+mod bitbucket_server_contributions;
+mod cli;
 mod colors;
+mod config;
 mod errors;
+mod gerrit_contributions;
+mod gitea_api;
 mod gitea_contributions;
 mod github_contributions;
 mod gitlab_contributions;
+mod http;
+mod json_heatmap_contributions;
+mod launchpad_contributions;
+mod local_contributions;
+mod pagure_contributions;
 mod processor;
+mod provider;
 mod renderer;
+mod secret;
+mod sourcehut_contributions;
 mod types;
 
 use renderer::ContributionGraphRenderer;
 
-trait ProviderConfig {
-    fn from_env() -> Result<Self, ProviderError>
-    where
-        Self: Sized;
-    fn server(&self) -> &str;
-    fn username(&self) -> &str;
-    fn token(&self) -> &str;
-}
-
-#[derive(Debug)]
-struct GenericConfig {
-    server: String,
-    username: String,
-    token: String,
-}
-
-impl GenericConfig {
-    fn new(server_var: &str, username_var: &str, token_var: &str) -> Result<Self, ProviderError> {
-        Ok(Self {
-            server: dotenv::var(server_var)
-                .map_err(|_| ProviderError::ConfigError(format!("{} must be set", server_var)))?
-                .trim_end_matches('/')
-                .to_string(),
-            username: dotenv::var(username_var)
-                .map_err(|_| ProviderError::ConfigError(format!("{} must be set", username_var)))?,
-            token: dotenv::var(token_var)
-                .map_err(|_| ProviderError::ConfigError(format!("{} must be set", token_var)))?,
-        })
-    }
-}
-
-impl ProviderConfig for GenericConfig {
-    fn from_env() -> Result<Self, ProviderError> {
-        Self::new("SERVER", "USERNAME", "TOKEN") // Override per provider
-    }
-    fn server(&self) -> &str {
-        &self.server
-    }
-    fn username(&self) -> &str {
-        &self.username
-    }
-    fn token(&self) -> &str {
-        &self.token
-    }
-}
-
 // Data processing
 async fn process_contributions(
     collections: Vec<Option<ContributionCollection>>,
@@ -98,49 +72,312 @@ async fn process_contributions(
     (contributions_per_row, max_contributions)
 }
 
-#[tokio::main]
-async fn main() -> std::io::Result<()> {
-    let end_date = OffsetDateTime::now_utc();
-    let start_date = end_date
-        .checked_sub(Duration::days(365))
-        .expect("Failed to subtract 365 days");
+/// Reports a setting that cannot be used, before anything is fetched.
+fn invalid_setting(error: SettingsError) -> ExitCode {
+    eprintln!("{}", error);
+    ExitCode::from(2)
+}
+
+/// The range of the graph, from midnight of its first day until midnight after its last day
+/// (or now), both UTC. The first day is moved back to a Sunday, as the graph's columns are weeks.
+fn date_range(options: &Options) -> Result<(OffsetDateTime, OffsetDateTime), SettingsError> {
+    // Each date comes with where it was set, for errors about it.
+    let end = match options.to {
+        Some(end) => Some((end, "--to".to_string())),
+        None => config::date("END_DATE")?.map(|end| (end, config::describe("END_DATE"))),
+    };
+    let start = match options.from {
+        Some(start) => Some((start, "--from".to_string())),
+        None => config::date("START_DATE")?.map(|start| (start, config::describe("START_DATE"))),
+    };
+    let invalid = |key: &str, message: String| SettingsError::InvalidValue {
+        key: key.to_string(),
+        message,
+    };
+
+    let (end_date, last_day) = match &end {
+        Some((end, key)) => {
+            let end_date = end
+                .next_day()
+                .ok_or_else(|| invalid(key, "is too far in the future".to_string()))?;
+            (end_date.with_time(Time::MIDNIGHT).assume_utc(), *end)
+        }
+        None => {
+            let now = OffsetDateTime::now_utc();
+            (now, now.date())
+        }
+    };
+    let (start_date, start_key) = match start {
+        Some((start, key)) if start > last_day => {
+            let last_day = match &end {
+                Some((_, end_key)) => format!("{} ({})", last_day, end_key),
+                None => format!("{} (today)", last_day),
+            };
+            return Err(invalid(
+                &key,
+                format!("must not be after the last day, {}", last_day),
+            ));
+        }
+        Some((start, key)) => (start, key),
+        None => (
+            end_date.date().saturating_sub(Duration::days(365)),
+            end.as_ref()
+                .map_or_else(|| "--to".to_string(), |(_, key)| key.clone()),
+        ),
+    };
     let start_date = start_date
         .checked_sub(Duration::days(
             start_date.weekday().number_days_from_sunday() as i64,
         ))
-        .expect("Failed to subtract days to get to Sunday");
-
-    let contributions: Vec<Option<ContributionCollection>> = vec![
-        github_contributions::get_github_contributions(start_date, end_date)
-            .await
-            .ok(),
-        gitlab_contributions::get_gitlab_contributions(start_date, end_date)
-            .await
-            .ok(),
-        gitea_contributions::get_gitea_contributions(start_date, end_date)
-            .await
-            .ok(),
-        codeberg_contributions::get_codeberg_contributions(start_date, end_date)
-            .await
-            .ok(),
-    ];
-
-    let (contributions_per_row, max_contributions) = process_contributions(contributions).await;
+        .ok_or_else(|| invalid(&start_key, "is too far in the past".to_string()))?
+        .with_time(Time::MIDNIGHT)
+        .assume_utc();
 
-    let mut renderer = ContributionGraphRenderer::new(
-        ColorScheme::find_by_name(
-            dotenv::var("COLOR_SCHEME")
-                .unwrap_or("github".to_string())
-                .as_str(),
-        )
-        .expect("Color scheme not found")
-        .colors
+    // The graph fills its last week up to seven days, those have to be dates as well.
+    let weeks = ((end_date - start_date).whole_days() + 6) / 7;
+    if start_date.checked_add(Duration::weeks(weeks)).is_none() {
+        let end_key = end.map_or_else(|| "--to".to_string(), |(_, key)| key);
+        return Err(invalid(&end_key, "is too far in the future".to_string()));
+    }
+    Ok((start_date, end_date))
+}
+
+fn color_scheme(options: &Options) -> Result<&'static ColorScheme, SettingsError> {
+    if let Some(scheme) = options.color_scheme {
+        return Ok(scheme);
+    }
+    let Some(name) = config::var("COLOR_SCHEME") else {
+        return Ok(ColorScheme::default());
+    };
+    ColorScheme::find_by_name(&name).ok_or_else(|| SettingsError::InvalidValue {
+        key: config::describe("COLOR_SCHEME"),
+        message: format!("names unknown color scheme {}", name),
+    })
+}
+
+/// Loads and fetches the account called `name` of `entry`, which together may take as long
+/// as the account's timeout. Loading counts as well, since reading secrets may run commands.
+async fn fetch_account(
+    entry: &'static ProviderEntry,
+    name: Option<String>,
+    label: String,
+    start_date: OffsetDateTime,
+    end_date: OffsetDateTime,
+) -> (ProviderReport, Option<ContributionCollection>) {
+    let timeout = match entry.timeout(name.as_deref()) {
+        Ok(timeout) => timeout,
+        Err(error) => {
+            return (
+                ProviderReport::new(label, ProviderStatus::Failed(error)),
+                None,
+            );
+        }
+    };
+    let deadline = tokio::time::Instant::now() + timeout;
+    let account = match tokio::time::timeout_at(deadline, entry.load_account(name.as_deref())).await
+    {
+        Ok(Ok(account)) => account,
+        Ok(Err(error)) => {
+            return (
+                ProviderReport::new(label, ProviderStatus::Failed(error)),
+                None,
+            );
+        }
+        Err(_) => {
+            return (
+                ProviderReport::new(label, ProviderStatus::TimedOut(timeout)),
+                None,
+            );
+        }
+    };
+
+    let result = tokio::time::timeout_at(
+        deadline,
+        provider::get_contributions(&account, start_date, end_date),
+    )
+    .await;
+    let (status, collection) = match result {
+        Ok(Ok(collection)) => (ProviderStatus::Completed, Some(collection)),
+        Ok(Err(error)) => (ProviderStatus::Failed(error), None),
+        Err(_) => (ProviderStatus::TimedOut(timeout), None),
+    };
+    let report = ProviderReport {
+        provider: account.label,
+        status,
+        quota: account.provider.quota(),
+    };
+    (report, collection)
+}
+
+/// Fetches the included accounts of every configured provider concurrently, so a slow server
+/// only delays itself.
+async fn fetch(
+    options: &Options,
+    start_date: OffsetDateTime,
+    end_date: OffsetDateTime,
+) -> (Vec<ContributionCollection>, Vec<ProviderReport>) {
+    let mut reports = Vec::new();
+    let mut tasks = Vec::new();
+    for entry in provider::REGISTRY {
+        if !options.includes_provider(entry) {
+            continue;
+        }
+        if !entry.is_configured() {
+            reports.push(ProviderReport::new(
+                entry.name.to_string(),
+                ProviderStatus::NotConfigured,
+            ));
+            continue;
+        }
+
+        let names = match entry.account_names() {
+            Some(names) => names.into_iter().map(Some).collect(),
+            None => vec![None],
+        };
+        for name in names {
+            if !options.includes(entry, name.as_deref()) {
+                continue;
+            }
+            // Keep the label, so a task that panics can still be reported.
+            let label = entry.label(name.as_deref());
+            let task = tokio::spawn(fetch_account(
+                entry,
+                name,
+                label.clone(),
+                start_date,
+                end_date,
+            ));
+            tasks.push((label, task));
+        }
+    }
+
+    let mut contributions = Vec::new();
+    for (label, task) in tasks {
+        match task.await {
+            Ok((report, collection)) => {
+                contributions.extend(collection);
+                reports.push(report);
+            }
+            Err(error) => reports.push(ProviderReport::new(
+                label,
+                ProviderStatus::Failed(error.into()),
+            )),
+        }
+    }
+    (contributions, reports)
+}
+
+fn exit_code(reports: &[ProviderReport]) -> ExitCode {
+    if reports.iter().any(|report| report.status.is_failure()) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+async fn show(options: &Options) -> std::io::Result<ExitCode> {
+    let (start_date, end_date) = match date_range(options) {
+        Ok(range) => range,
+        Err(error) => return Ok(invalid_setting(error)),
+    };
+    let color_scheme = match color_scheme(options) {
+        Ok(color_scheme) => color_scheme,
+        Err(error) => return Ok(invalid_setting(error)),
+    };
+    let verbose = options.verbose || config::flag("VERBOSE");
+    let strict = options.strict || config::flag("STRICT");
+
+    let (contributions, reports) = fetch(options, start_date, end_date).await;
+    let failed = reports.iter().any(|report| report.status.is_failure());
+
+    let breakdowns: Vec<(String, ContributionBreakdown)> = contributions
         .iter()
-        .map(|&s| s.to_string())
-        .collect(),
+        .filter_map(|collection| {
+            let breakdown = collection.breakdown.clone()?;
+            Some((collection.provider.clone(), breakdown))
+        })
+        .collect();
+    let (mut contributions_per_row, max_contributions) =
+        process_contributions(contributions.into_iter().map(Some).collect()).await;
+    // The last week ends with the end date, rather than running to Saturday.
+    let last_day = (end_date - Duration::nanoseconds(1)).date();
+    let last_day = format!(
+        "{}-{:02}-{:02}",
+        last_day.year(),
+        last_day.month() as u8,
+        last_day.day()
     );
-    renderer.render_months(&contributions_per_row)?;
-    renderer.render_graph(&contributions_per_row, max_contributions)?;
+    for row in &mut contributions_per_row {
+        while row
+            .last()
+            .is_some_and(|day| day.as_ref().is_some_and(|day| day.date > last_day))
+        {
+            row.pop();
+        }
+    }
 
-    Ok(())
+    let mut renderer = ContributionGraphRenderer::new(
+        color_scheme.colors.iter().map(|&s| s.to_string()).collect(),
+    );
+    // A partial graph looks like a quiet year, strict mode only shows complete ones.
+    let incomplete = strict && failed;
+    if options.format == Format::Json {
+        let error = incomplete.then_some("not every provider could be fetched");
+        renderer.render_json(&contributions_per_row, &breakdowns, &reports, error)?;
+        return Ok(exit_code(&reports));
+    }
+
+    if !incomplete {
+        renderer.render_months(&contributions_per_row)?;
+        renderer.render_graph(&contributions_per_row, max_contributions)?;
+        renderer.render_week_kinds(&contributions_per_row, &breakdowns)?;
+        renderer.render_breakdowns(&breakdowns)?;
+    }
+    renderer.render_status(&reports, verbose)?;
+    if incomplete {
+        eprintln!("Not showing the graph because not every provider could be fetched.");
+    }
+    Ok(exit_code(&reports))
+}
+
+/// Fetches the last week of every configured account, to check that their settings work.
+async fn doctor(options: &Options) -> std::io::Result<ExitCode> {
+    match config::path() {
+        Some(path) => println!("Using configuration file {}", path.display()),
+        None => println!("No configuration file found"),
+    }
+    let end_date = OffsetDateTime::now_utc();
+    let start_date = (end_date - Duration::days(7))
+        .date()
+        .with_time(Time::MIDNIGHT)
+        .assume_utc();
+    let (_, reports) = fetch(options, start_date, end_date).await;
+
+    let mut renderer = ContributionGraphRenderer::new(Vec::new());
+    renderer.render_status(&reports, true)?;
+    Ok(exit_code(&reports))
+}
+
+#[tokio::main]
+async fn main() -> std::io::Result<ExitCode> {
+    let cli = Cli::parse();
+    // Load `.env` and the configuration file up front, whether a provider is configured is
+    // decided from them.
+    dotenv::dotenv().ok();
+    if let Err(error) = config::load() {
+        return Ok(invalid_setting(error));
+    }
+
+    match cli.command.unwrap_or(Command::Show) {
+        Command::Show => show(&cli.options).await,
+        Command::ListSchemes => {
+            ContributionGraphRenderer::new(Vec::new()).render_schemes(colors::SCHEMES)?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Providers => {
+            ContributionGraphRenderer::new(Vec::new()).render_providers(provider::REGISTRY)?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Doctor => doctor(&cli.options).await,
+    }
 }
@@ -0,0 +1,109 @@
+use crate::{
+    ProviderError,
+    http::{HttpClient, Quota},
+    processor::Event,
+    provider::{BoxFuture, Events, Provider, into_events, required_var, secret_var, var},
+    secret::Secret,
+};
+use std::collections::HashMap;
+use time::OffsetDateTime;
+
+#[derive(Debug)]
+struct PagureConfig {
+    server: String,
+    username: String,
+    token: Option<Secret>,
+}
+
+impl PagureConfig {
+    async fn from_env(prefix: &str) -> Result<Self, ProviderError> {
+        Ok(Self {
+            server: var(prefix, "SERVER")
+                .unwrap_or_else(|| "https://pagure.io".to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            username: required_var(prefix, "USERNAME")?,
+            token: secret_var(prefix, "TOKEN").await?,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct PagureDay {
+    date: String,
+    count: i64,
+}
+
+impl Event for PagureDay {
+    fn timestamp(&self) -> Result<OffsetDateTime, ProviderError> {
+        time::Date::parse(
+            &self.date,
+            &time::format_description::well_known::Iso8601::DATE,
+        )
+        .map(|date| date.midnight().assume_utc())
+        .map_err(|e| ProviderError::DateError(format!("Failed to parse date: {}", e)))
+    }
+    fn contributions(&self) -> i64 {
+        self.count
+    }
+}
+
+pub struct PagureClient {
+    client: HttpClient,
+    config: PagureConfig,
+}
+
+impl PagureClient {
+    fn new(config: PagureConfig) -> Self {
+        Self {
+            client: HttpClient::new("Pagure"),
+            config,
+        }
+    }
+}
+
+impl Provider for PagureClient {
+    fn name(&self) -> &str {
+        "Pagure"
+    }
+
+    async fn from_env(prefix: &str) -> Result<Self, ProviderError> {
+        Ok(Self::new(PagureConfig::from_env(prefix).await?))
+    }
+
+    fn fetch_events(
+        &self,
+        _start_date: OffsetDateTime,
+        _end_date: OffsetDateTime,
+    ) -> BoxFuture<'_, Result<Events, ProviderError>> {
+        Box::pin(async move {
+            let endpoint = format!(
+                "{}/api/0/user/{}/activity/stats",
+                self.config.server, self.config.username
+            );
+
+            let mut request = self
+                .client
+                .get(endpoint)
+                .query(&[("format", "isoformat"), ("tz", "UTC")])
+                .header("accept", "application/json");
+            if let Some(token) = &self.config.token {
+                request = request.header("Authorization", format!("token {}", token.expose()));
+            }
+            let response = self.client.send(request).await?;
+            let response = self.client.error_for_status(response).await?;
+
+            // The statistics are a single object mapping each active day to its number of actions.
+            let stats: HashMap<String, i64> = self.client.json(response).await?;
+            let events: Vec<PagureDay> = stats
+                .into_iter()
+                .map(|(date, count)| PagureDay { date, count })
+                .collect();
+            Ok(into_events(events))
+        })
+    }
+
+    fn quota(&self) -> Option<Quota> {
+        self.client.quota()
+    }
+}
@@ -1,3 +1,4 @@
+use crate::types::ContributionKind;
 use crate::{ContributionDay, ProviderError};
 use time::{Duration, OffsetDateTime};
 
@@ -34,9 +35,12 @@ impl ContributionProcessor {
                     ),
                     weekday: current_date.weekday().number_days_from_sunday() as i64,
                 });
-                current_date = current_date
-                    .checked_add(Duration::days(1))
-                    .expect("Failed to increment date");
+                // There are no days after the last date `time` can represent.
+                let Some(next_date) = current_date.checked_add(Duration::days(1)) else {
+                    contributions.push((week_number, week_days));
+                    return contributions;
+                };
+                current_date = next_date;
             }
 
             contributions.push((week_number, week_days));
@@ -46,7 +50,8 @@ impl ContributionProcessor {
         contributions
     }
 
-    pub fn process_events<T: Event + std::fmt::Debug>(
+    #[allow(clippy::type_complexity)]
+    pub fn process_events<T: Event>(
         &self,
         events: Vec<T>,
         mut calendar: Vec<(i64, Vec<ContributionDay>)>,
@@ -76,7 +81,24 @@ impl ContributionProcessor {
     }
 }
 
-pub trait Event {
+pub trait Event: std::fmt::Debug {
     fn timestamp(&self) -> Result<OffsetDateTime, ProviderError>;
     fn contributions(&self) -> i64;
+
+    /// What the event was, for providers that can tell.
+    fn kind(&self) -> Option<ContributionKind> {
+        None
+    }
+}
+
+impl<E: Event + ?Sized> Event for Box<E> {
+    fn timestamp(&self) -> Result<OffsetDateTime, ProviderError> {
+        (**self).timestamp()
+    }
+    fn contributions(&self) -> i64 {
+        (**self).contributions()
+    }
+    fn kind(&self) -> Option<ContributionKind> {
+        (**self).kind()
+    }
 }
@@ -0,0 +1,532 @@
+use crate::config;
+use crate::errors::ProviderError;
+use crate::http::Quota;
+use crate::processor::{ContributionProcessor, Event};
+use crate::secret::{self, Secret};
+use crate::types::{ContributionBreakdown, ContributionCollection};
+use crate::{
+    azure_devops_contributions, bitbucket_contributions, bitbucket_server_contributions,
+    gerrit_contributions, gitea_contributions, github_contributions, gitlab_contributions,
+    json_heatmap_contributions, launchpad_contributions, local_contributions, pagure_contributions,
+    sourcehut_contributions,
+};
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+pub type Events = Vec<Box<dyn Event + Send>>;
+
+/// Prefix of the provider that has a named account with settings below `prefix`, such as
+/// `GITLAB` for `GITLAB_WORK`.
+fn provider_prefix(prefix: &str) -> Option<&'static str> {
+    REGISTRY.iter().find_map(|entry| {
+        entry
+            .account_names()?
+            .iter()
+            .any(|name| entry.account_prefix(Some(name)) == prefix)
+            .then_some(entry.prefix)
+    })
+}
+
+/// The variable holding the `<PREFIX>_<NAME>` setting. A named account, such as
+/// `GITLAB_WORK`, shares the settings of its provider it does not set itself, such as
+/// `GITLAB_TIMEOUT`.
+fn setting_variable(prefix: &str, name: &str) -> String {
+    let variable = config::variable(prefix, name);
+    if config::var(&variable).is_none()
+        && let Some(provider_prefix) = provider_prefix(prefix)
+    {
+        let shared = config::variable(provider_prefix, name);
+        if config::var(&shared).is_some() {
+            return shared;
+        }
+    }
+    variable
+}
+
+/// Reads the `<PREFIX>_<NAME>` variable, such as `GITLAB_TOKEN` or `GITLAB_WORK_TOKEN`, or
+/// else the corresponding setting of the configuration file. Named accounts fall back to the
+/// setting of their provider.
+pub fn var(prefix: &str, name: &str) -> Option<String> {
+    config::var(&setting_variable(prefix, name))
+}
+
+/// Names where the `<PREFIX>_<NAME>` setting was set, for errors about its value.
+pub fn describe(prefix: &str, name: &str) -> String {
+    config::describe(&setting_variable(prefix, name))
+}
+
+/// Like [`var`], but a missing variable is a configuration error.
+pub fn required_var(prefix: &str, name: &str) -> Result<String, ProviderError> {
+    var(prefix, name).ok_or_else(|| {
+        let variable = match provider_prefix(prefix) {
+            Some(provider_prefix) => format!(
+                "{} or {}",
+                config::variable(prefix, name),
+                config::variable(provider_prefix, name)
+            ),
+            None => config::variable(prefix, name),
+        };
+        ProviderError::ConfigError(format!("{} must be set", variable))
+    })
+}
+
+/// Reads the `<PREFIX>_<NAME>` weight, such as `GITLAB_WEIGHT_PUSHED`, which is `default`
+/// unless set. Negative weights would take contributions away from days, so they are rejected.
+pub fn weight_var(prefix: &str, name: &str, default: i64) -> Result<i64, ProviderError> {
+    let Some(value) = var(prefix, name) else {
+        return Ok(default);
+    };
+    match value.trim().parse() {
+        Ok(weight) if weight >= 0 => Ok(weight),
+        _ => Err(ProviderError::ConfigError(format!(
+            "{} must be a whole number of 0 or more",
+            describe(prefix, name)
+        ))),
+    }
+}
+
+/// Suffixes of the settings pointing at where a secret is, and how to read it from there.
+const SECRET_SOURCES: &[(&str, secret::Source)] = &[
+    ("FILE", secret::Source::File),
+    ("COMMAND", secret::Source::Command),
+    ("GIT_CREDENTIAL", secret::Source::GitCredential),
+    ("KEYRING", secret::Source::Keyring),
+];
+
+/// Reads the `<PREFIX>_<NAME>` secret, such as `GITLAB_TOKEN`, or else from where one of
+/// these points:
+/// - `<PREFIX>_<NAME>_FILE`, a file holding the secret
+/// - `<PREFIX>_<NAME>_COMMAND`, a command printing it, such as `pass show gitlab`
+/// - `<PREFIX>_<NAME>_GIT_CREDENTIAL`, a URL whose password git's credential helpers know
+/// - `<PREFIX>_<NAME>_KEYRING`, attributes to look it up by in the Secret Service keyring
+pub async fn secret_var(prefix: &str, name: &str) -> Result<Option<Secret>, ProviderError> {
+    if let Some(value) = var(prefix, name) {
+        return Ok(Some(Secret::new(value)));
+    }
+    for (suffix, source) in SECRET_SOURCES {
+        let setting = format!("{}_{}", name, suffix);
+        if let Some(value) = var(prefix, &setting) {
+            return source
+                .read(&value, &describe(prefix, &setting))
+                .await
+                .map(Some);
+        }
+    }
+    Ok(None)
+}
+
+/// Like [`secret_var`], but a missing secret is a configuration error.
+pub async fn required_secret(prefix: &str, name: &str) -> Result<Secret, ProviderError> {
+    secret_var(prefix, name).await?.ok_or_else(|| {
+        ProviderError::ConfigError(format!(
+            "{0}_{1} or one of {0}_{1}_FILE, {0}_{1}_COMMAND, {0}_{1}_GIT_CREDENTIAL and \
+             {0}_{1}_KEYRING must be set",
+            prefix, name
+        ))
+    })
+}
+
+/// Server, username and token of a provider account.
+pub trait ProviderConfig {
+    fn server(&self) -> &str;
+    fn username(&self) -> &str;
+    fn token(&self) -> &str;
+}
+
+#[derive(Debug)]
+pub struct GenericConfig {
+    server: String,
+    username: String,
+    token: Secret,
+}
+
+impl GenericConfig {
+    /// Reads `<PREFIX>_SERVER`, `<PREFIX>_USERNAME` and `<PREFIX>_TOKEN`.
+    pub async fn from_env(prefix: &str) -> Result<Self, ProviderError> {
+        Ok(Self {
+            server: required_var(prefix, "SERVER")?
+                .trim_end_matches('/')
+                .to_string(),
+            username: required_var(prefix, "USERNAME")?,
+            token: required_secret(prefix, "TOKEN").await?,
+        })
+    }
+}
+
+impl ProviderConfig for GenericConfig {
+    fn server(&self) -> &str {
+        &self.server
+    }
+    fn username(&self) -> &str {
+        &self.username
+    }
+    fn token(&self) -> &str {
+        self.token.expose()
+    }
+}
+
+/// A source of contributions, such as a forge or a set of local repositories.
+pub trait Provider: Send + Sync {
+    /// Name the provider's contributions are labelled with.
+    fn name(&self) -> &str;
+
+    /// Loads the provider's configuration from the `<PREFIX>_*` environment variables. Secrets
+    /// may be read by running commands, so this is awaited within the account's timeout.
+    fn from_env(prefix: &str) -> impl Future<Output = Result<Self, ProviderError>> + Send
+    where
+        Self: Sized;
+
+    /// Fetches the contribution events between `start_date` and `end_date`.
+    fn fetch_events(
+        &self,
+        start_date: OffsetDateTime,
+        end_date: OffsetDateTime,
+    ) -> BoxFuture<'_, Result<Events, ProviderError>>;
+
+    /// Fetches what the contributions between `start_date` and `end_date` consist of, for
+    /// providers that can tell.
+    fn fetch_breakdown(
+        &self,
+        _start_date: OffsetDateTime,
+        _end_date: OffsetDateTime,
+    ) -> BoxFuture<'_, Result<Option<ContributionBreakdown>, ProviderError>> {
+        Box::pin(async { Ok(None) })
+    }
+
+    /// The rate limit quota the provider's server reported last, if it reports one.
+    fn quota(&self) -> Option<Quota> {
+        None
+    }
+}
+
+/// Boxes the events of a single type so they can be returned from [`Provider::fetch_events`].
+pub fn into_events<E: Event + Send + 'static>(events: Vec<E>) -> Events {
+    events
+        .into_iter()
+        .map(|event| Box::new(event) as Box<dyn Event + Send>)
+        .collect()
+}
+
+/// Seconds a provider may take to fetch its contributions, unless configured otherwise.
+const DEFAULT_TIMEOUT_SECONDS: u64 = 30;
+
+/// Loads an account of the provider with the given name, from the settings below the given
+/// prefix.
+type ProviderLoader =
+    for<'a> fn(&'static str, &'a str) -> BoxFuture<'a, Result<Box<dyn Provider>, ProviderError>>;
+
+fn load<'a, P: Provider + 'static>(
+    _name: &'static str,
+    prefix: &'a str,
+) -> BoxFuture<'a, Result<Box<dyn Provider>, ProviderError>> {
+    Box::pin(async move { Ok(Box::new(P::from_env(prefix).await?) as Box<dyn Provider>) })
+}
+
+/// Loads a member of the Gitea family, which is told apart by the name of its entry alone.
+fn load_gitea_family<'a>(
+    name: &'static str,
+    prefix: &'a str,
+) -> BoxFuture<'a, Result<Box<dyn Provider>, ProviderError>> {
+    Box::pin(async move {
+        let provider = gitea_contributions::GiteaFamilyProvider::load(name, prefix).await?;
+        Ok(Box::new(provider) as Box<dyn Provider>)
+    })
+}
+
+/// A configured account of one of the providers.
+pub struct Account {
+    /// Label of the account's contributions, such as `GitLab (work)`.
+    pub label: String,
+    pub provider: Box<dyn Provider>,
+}
+
+pub struct ProviderEntry {
+    pub name: &'static str,
+    /// Prefix of the provider's environment variables, such as `GITLAB`.
+    pub prefix: &'static str,
+    /// Settings the provider reads, such as `USERNAME` for `GITLAB_USERNAME`.
+    pub settings: &'static [&'static str],
+    /// Settings the provider reads through [`secret_var`], which can also be read from where
+    /// `<NAME>_FILE` and the like point.
+    pub secrets: &'static [&'static str],
+    pub load: ProviderLoader,
+}
+
+/// Settings of the providers that read nothing but a server and a username, besides secrets.
+const SERVER_SETTINGS: &[&str] = &["SERVER", "USERNAME"];
+
+impl ProviderEntry {
+    /// Names of the settings of an account of the provider, including where its secrets can be
+    /// read from, but not the `ACCOUNTS` and `TIMEOUT` every provider has.
+    pub fn setting_names(&self) -> impl Iterator<Item = String> + '_ {
+        let secrets = self.secrets.iter().flat_map(|secret| {
+            std::iter::once(secret.to_string()).chain(
+                SECRET_SOURCES
+                    .iter()
+                    .map(move |(suffix, _)| format!("{}_{}", secret, suffix)),
+            )
+        });
+        self.settings
+            .iter()
+            .map(|setting| setting.to_string())
+            .chain(secrets)
+    }
+
+    /// Whether any of the provider's own settings are set. Providers without any are not
+    /// configured, rather than failing to load. Other variables sharing the prefix, such as
+    /// `GITHUB_ACTIONS` on CI runners, do not count.
+    pub fn is_configured(&self) -> bool {
+        var(self.prefix, "ACCOUNTS").is_some()
+            || self
+                .setting_names()
+                .any(|name| var(self.prefix, &name).is_some())
+    }
+
+    /// Names of the accounts listed in `<PREFIX>_ACCOUNTS`, each configured through
+    /// `<PREFIX>_<ACCOUNT>_*` variables. Without it, `None`: a single account is configured
+    /// through the `<PREFIX>_*` variables.
+    pub fn account_names(&self) -> Option<Vec<String>> {
+        // Read directly, since [`var`] looks up the accounts to fall back to the provider.
+        let names = config::var(&config::variable(self.prefix, "ACCOUNTS"))?;
+        Some(
+            names
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+                .collect(),
+        )
+    }
+
+    /// Prefix of the settings of the account called `name`, or of the single unnamed account.
+    fn account_prefix(&self, name: Option<&str>) -> String {
+        match name {
+            Some(name) => config::variable(self.prefix, name),
+            None => self.prefix.to_string(),
+        }
+    }
+
+    /// Label of the account called `name` until it is loaded, such as `GitLab (work)`.
+    pub fn label(&self, name: Option<&str>) -> String {
+        account_label(self.name, name)
+    }
+
+    /// How long the account called `name` may take, from `<PREFIX>_<ACCOUNT>_TIMEOUT`,
+    /// `<PREFIX>_TIMEOUT` or else `PROVIDER_TIMEOUT` (in seconds).
+    pub fn timeout(&self, name: Option<&str>) -> Result<Duration, ProviderError> {
+        let prefix = self.account_prefix(name);
+        let (seconds, setting) = match var(&prefix, "TIMEOUT") {
+            Some(seconds) => (seconds, describe(&prefix, "TIMEOUT")),
+            None => match config::var("PROVIDER_TIMEOUT") {
+                Some(seconds) => (seconds, config::describe("PROVIDER_TIMEOUT")),
+                None => return Ok(Duration::from_secs(DEFAULT_TIMEOUT_SECONDS)),
+            },
+        };
+        match seconds.trim().parse() {
+            Ok(seconds) if seconds > 0 => Ok(Duration::from_secs(seconds)),
+            _ => Err(ProviderError::ConfigError(format!(
+                "{} must be a positive number of seconds",
+                setting
+            ))),
+        }
+    }
+
+    /// Loads the account called `name`, or the single unnamed account.
+    pub async fn load_account(&self, name: Option<&str>) -> Result<Account, ProviderError> {
+        let provider = (self.load)(self.name, &self.account_prefix(name)).await?;
+        Ok(Account {
+            label: account_label(provider.name(), name),
+            provider,
+        })
+    }
+}
+
+fn account_label(provider_name: &str, name: Option<&str>) -> String {
+    match name {
+        Some(name) => format!("{} ({})", provider_name, name),
+        None => provider_name.to_string(),
+    }
+}
+
+/// Every known provider, in the order they are listed.
+pub const REGISTRY: &[ProviderEntry] = &[
+    ProviderEntry {
+        name: "GitHub",
+        prefix: "GITHUB",
+        settings: SERVER_SETTINGS,
+        secrets: &["TOKEN"],
+        load: load::<github_contributions::GitHubClient>,
+    },
+    ProviderEntry {
+        name: "GitLab",
+        prefix: "GITLAB",
+        settings: gitlab_contributions::SETTINGS,
+        secrets: &["TOKEN"],
+        load: load::<gitlab_contributions::GitLabClient>,
+    },
+    ProviderEntry {
+        name: "Gitea",
+        prefix: "GITEA",
+        settings: SERVER_SETTINGS,
+        secrets: &["TOKEN"],
+        load: load_gitea_family,
+    },
+    ProviderEntry {
+        name: "Codeberg",
+        prefix: "CODEBERG",
+        settings: SERVER_SETTINGS,
+        secrets: &["TOKEN"],
+        load: load_gitea_family,
+    },
+    ProviderEntry {
+        name: "Forgejo",
+        prefix: "FORGEJO",
+        settings: SERVER_SETTINGS,
+        secrets: &["TOKEN"],
+        load: load_gitea_family,
+    },
+    ProviderEntry {
+        name: "Gogs",
+        prefix: "GOGS",
+        settings: SERVER_SETTINGS,
+        secrets: &["TOKEN"],
+        load: load_gitea_family,
+    },
+    ProviderEntry {
+        name: "Bitbucket",
+        prefix: "BITBUCKET",
+        settings: &["USERNAME", "WORKSPACE", "EMAIL"],
+        secrets: &["APP_PASSWORD", "TOKEN"],
+        load: load::<bitbucket_contributions::BitbucketClient>,
+    },
+    ProviderEntry {
+        name: "Bitbucket Server",
+        prefix: "BITBUCKET_DC",
+        settings: &["SERVER", "USERNAME", "EMAIL"],
+        secrets: &["TOKEN"],
+        load: load::<bitbucket_server_contributions::BitbucketServerClient>,
+    },
+    ProviderEntry {
+        name: "Local",
+        prefix: "LOCAL",
+        settings: &[
+            "REPOSITORIES",
+            "AUTHOR_EMAILS",
+            "AUTHOR_NAMES",
+            "USE_COMMITTER_DATE",
+        ],
+        secrets: &[],
+        load: load::<local_contributions::LocalClient>,
+    },
+    ProviderEntry {
+        name: "Azure DevOps",
+        prefix: "AZURE_DEVOPS",
+        settings: SERVER_SETTINGS,
+        secrets: &["TOKEN"],
+        load: load::<azure_devops_contributions::AzureDevOpsClient>,
+    },
+    ProviderEntry {
+        name: "SourceHut",
+        prefix: "SOURCEHUT",
+        settings: &["SERVER", "USERNAME", "EMAILS"],
+        secrets: &["TOKEN"],
+        load: load::<sourcehut_contributions::SourceHutClient>,
+    },
+    ProviderEntry {
+        name: "Gerrit",
+        prefix: "GERRIT",
+        settings: &[
+            "SERVER",
+            "USERNAME",
+            "WEIGHT_CREATED",
+            "WEIGHT_PATCHSET",
+            "WEIGHT_MERGED",
+            "WEIGHT_REVIEW",
+        ],
+        secrets: &["TOKEN"],
+        load: load::<gerrit_contributions::GerritClient>,
+    },
+    ProviderEntry {
+        name: "Pagure",
+        prefix: "PAGURE",
+        settings: SERVER_SETTINGS,
+        secrets: &["TOKEN"],
+        load: load::<pagure_contributions::PagureClient>,
+    },
+    ProviderEntry {
+        name: "Launchpad",
+        prefix: "LAUNCHPAD",
+        settings: SERVER_SETTINGS,
+        secrets: &[],
+        load: load::<launchpad_contributions::LaunchpadClient>,
+    },
+    ProviderEntry {
+        name: "JSON",
+        prefix: "JSON_HEATMAP",
+        settings: &[
+            "NAME",
+            "URL",
+            "ITEMS_PATH",
+            "DATE_FIELD",
+            "COUNT_FIELD",
+            "DATE_FORMAT",
+        ],
+        secrets: &["AUTH_HEADER"],
+        load: load::<json_heatmap_contributions::JsonHeatmapClient>,
+    },
+];
+
+/// Tallies the kinds of the events within the range, in total and per week of the graph, for
+/// providers that tell them apart.
+fn tally_kinds(
+    events: &Events,
+    start_date: OffsetDateTime,
+    end_date: OffsetDateTime,
+) -> Result<Option<ContributionBreakdown>, ProviderError> {
+    let mut breakdown = None;
+    for event in events {
+        let Some(kind) = event.kind() else {
+            continue;
+        };
+        let timestamp = event.timestamp()?;
+        if timestamp < start_date || timestamp >= end_date {
+            continue;
+        }
+        let week = (timestamp - start_date).whole_days() as usize / 7;
+        breakdown
+            .get_or_insert_with(ContributionBreakdown::default)
+            .add(week, kind, event.contributions());
+    }
+    Ok(breakdown)
+}
+
+pub async fn get_contributions(
+    account: &Account,
+    start_date: OffsetDateTime,
+    end_date: OffsetDateTime,
+) -> Result<ContributionCollection, ProviderError> {
+    let (events, breakdown) = tokio::join!(
+        account.provider.fetch_events(start_date, end_date),
+        account.provider.fetch_breakdown(start_date, end_date)
+    );
+    let events = events?;
+    // The graph is still worth showing without a breakdown.
+    let breakdown = match breakdown {
+        Ok(Some(breakdown)) => Some(breakdown),
+        _ => tally_kinds(&events, start_date, end_date)?,
+    };
+
+    let processor = ContributionProcessor::new(start_date, end_date);
+    let calendar = processor.initialize_contribution_calendar();
+    let (contributions, max_contributions) = processor.process_events(events, calendar)?;
+
+    Ok(ContributionCollection {
+        provider: account.label.clone(),
+        contributions,
+        max_contributions,
+        breakdown,
+    })
+}
@@ -1,7 +1,12 @@
-use crate::types::ContributionDay;
-use std::io::Write;
+use crate::colors::ColorScheme;
+use crate::provider::ProviderEntry;
+use crate::types::{
+    ContributionBreakdown, ContributionDay, ContributionKind, KindCounts, ProviderReport,
+    ProviderStatus,
+};
+use serde_json::json;
+use std::io::{IsTerminal, Write};
 use termcolor::{ColorChoice, ColorSpec, StandardStream, WriteColor};
-use time::OffsetDateTime;
 
 pub trait HexToRGB {
     fn to_rgb(&self) -> (u8, u8, u8);
@@ -24,8 +29,14 @@ pub struct ContributionGraphRenderer {
 
 impl ContributionGraphRenderer {
     pub fn new(color_scheme: Vec<String>) -> Self {
+        // Output that is piped into another program is left without escape sequences.
+        let color_choice = if std::io::stdout().is_terminal() {
+            ColorChoice::Auto
+        } else {
+            ColorChoice::Never
+        };
         Self {
-            stdout: StandardStream::stdout(ColorChoice::Always),
+            stdout: StandardStream::stdout(color_choice),
             color_scheme,
         }
     }
@@ -44,18 +55,6 @@ impl ContributionGraphRenderer {
         if let Some(row) = contributions_per_row.last() {
             for contribution in row {
                 if let Some(day) = contribution {
-                    if let Some(contribution_day) = contribution {
-                        if contribution_day.date == row.last().unwrap().as_ref().unwrap().date
-                            && contribution_day.weekday
-                                > OffsetDateTime::now_utc()
-                                    .weekday()
-                                    .number_days_from_sunday()
-                                    as i64
-                        {
-                            break;
-                        }
-                    }
-
                     let date_parts: Vec<&str> = day.date.split('-').collect();
                     if date_parts.len() >= 3 {
                         let month_idx = date_parts[1].parse::<usize>().unwrap_or(1) - 1;
@@ -89,16 +88,6 @@ impl ContributionGraphRenderer {
     ) -> std::io::Result<()> {
         for row in contributions_per_row {
             for contribution in row {
-                if let Some(contribution) = contribution {
-                    if contribution.date == row.last().unwrap().as_ref().unwrap().date
-                        && contribution.weekday
-                            > OffsetDateTime::now_utc()
-                                .weekday()
-                                .number_days_from_sunday() as i64
-                    {
-                        break;
-                    }
-                }
                 let color_index = contribution.as_ref().map_or(0, |c| {
                     ((c.contribution_count as f64 / max_contributions as f64)
                         * (self.color_scheme.len() - 1) as f64)
@@ -121,4 +110,271 @@ impl ContributionGraphRenderer {
         }
         Ok(())
     }
+
+    /// Marks each week of the graph with the kind most of its contributions were, counting the
+    /// providers with a breakdown, and explains the marks.
+    pub fn render_week_kinds(
+        &mut self,
+        contributions_per_row: &[Vec<Option<ContributionDay>>],
+        breakdowns: &[(String, ContributionBreakdown)],
+    ) -> std::io::Result<()> {
+        let weeks = contributions_per_row.first().map_or(0, Vec::len);
+        let mut counts = vec![KindCounts::default(); weeks];
+        for (_, breakdown) in breakdowns {
+            for (week, week_counts) in counts.iter_mut().zip(&breakdown.weeks) {
+                week.merge(week_counts);
+            }
+        }
+        let kinds: Vec<Option<ContributionKind>> = counts.iter().map(KindCounts::most).collect();
+        if kinds.iter().all(Option::is_none) {
+            return Ok(());
+        }
+
+        self.stdout.reset()?;
+        for kind in &kinds {
+            write!(
+                self.stdout,
+                "{} ",
+                kind.map_or(' ', ContributionKind::symbol)
+            )?;
+        }
+        writeln!(self.stdout)?;
+        let legend: Vec<String> = ContributionKind::ALL
+            .into_iter()
+            .filter(|kind| kinds.contains(&Some(*kind)))
+            .map(|kind| format!("{} {}", kind.symbol(), kind.label()))
+            .collect();
+        writeln!(self.stdout, "Mostly {}", legend.join(", "))
+    }
+
+    /// Shows what the contributions of each provider with a breakdown consist of.
+    pub fn render_breakdowns(
+        &mut self,
+        breakdowns: &[(String, ContributionBreakdown)],
+    ) -> std::io::Result<()> {
+        self.stdout.reset()?;
+        for (provider, breakdown) in breakdowns {
+            self.stdout.set_color(ColorSpec::new().set_bold(true))?;
+            write!(self.stdout, "{}", provider)?;
+            self.stdout.reset()?;
+            let parts: Vec<String> = ContributionKind::ALL
+                .into_iter()
+                .map(|kind| (breakdown.kinds.get(kind), kind.label()))
+                .chain([(breakdown.restricted, "private contributions")])
+                .filter(|(count, _)| *count != 0)
+                .map(|(count, label)| format!("{} {}", count, label))
+                .collect();
+            if parts.is_empty() {
+                writeln!(self.stdout, ": no contributions")?;
+            } else {
+                writeln!(self.stdout, ": {}", parts.join(", "))?;
+            }
+            for (repository, commits) in &breakdown.repositories {
+                writeln!(self.stdout, "  {:>5} commits  {}", commits, repository)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Lists the providers that are missing from the graph and the rate limit quota providers
+    /// have left below the graph. Verbose output adds the causes of failures and the
+    /// providers that are not configured.
+    pub fn render_status(
+        &mut self,
+        reports: &[ProviderReport],
+        verbose: bool,
+    ) -> std::io::Result<()> {
+        self.stdout.reset()?;
+        for report in reports {
+            let (color, message) = match &report.status {
+                ProviderStatus::Completed if !verbose => continue,
+                ProviderStatus::Completed => (termcolor::Color::Green, "was fetched".to_string()),
+                ProviderStatus::NotConfigured if !verbose => continue,
+                ProviderStatus::NotConfigured => {
+                    (termcolor::Color::White, "is not configured".to_string())
+                }
+                ProviderStatus::TimedOut(timeout) => (
+                    termcolor::Color::Yellow,
+                    format!(
+                        "timed out after {}s and is missing from the graph",
+                        timeout.as_secs()
+                    ),
+                ),
+                ProviderStatus::Failed(error) => {
+                    (termcolor::Color::Red, format!("failed: {}", error))
+                }
+            };
+            self.stdout
+                .set_color(ColorSpec::new().set_fg(Some(color)))?;
+            write!(self.stdout, "{}", report.provider)?;
+            self.stdout.reset()?;
+            writeln!(self.stdout, " {}", message)?;
+
+            if let (true, ProviderStatus::Failed(error)) = (verbose, &report.status) {
+                // Causes are often already spelled out in the message of their error.
+                let mut shown = message;
+                let mut source = std::error::Error::source(error);
+                while let Some(cause) = source {
+                    let cause_message = cause.to_string();
+                    if !shown.contains(&cause_message) {
+                        writeln!(self.stdout, "    caused by: {}", cause_message)?;
+                        shown = cause_message;
+                    }
+                    source = cause.source();
+                }
+            }
+        }
+
+        for report in reports {
+            let Some(quota) = &report.quota else {
+                continue;
+            };
+            write!(self.stdout, "{}: {}", report.provider, quota.remaining)?;
+            if let Some(limit) = quota.limit {
+                write!(self.stdout, " of {}", limit)?;
+            }
+            write!(self.stdout, " requests left")?;
+            if let Some(reset) = quota.reset {
+                write!(
+                    self.stdout,
+                    ", resets at {:02}:{:02} UTC",
+                    reset.hour(),
+                    reset.minute()
+                )?;
+            }
+            writeln!(self.stdout)?;
+        }
+        Ok(())
+    }
+
+    /// Lists the color schemes, with a sample of their colors.
+    pub fn render_schemes(&mut self, schemes: &[ColorScheme]) -> std::io::Result<()> {
+        for scheme in schemes {
+            write!(self.stdout, "{:<16}", scheme.name)?;
+            for color in scheme.colors {
+                let (r, g, b) = color.to_string().to_rgb();
+                self.stdout
+                    .set_color(ColorSpec::new().set_fg(Some(termcolor::Color::Rgb(r, g, b))))?;
+                write!(self.stdout, "■ ")?;
+            }
+            self.stdout.reset()?;
+            writeln!(self.stdout)?;
+        }
+        Ok(())
+    }
+
+    /// Lists the providers with the prefix of their settings, and their configured accounts.
+    pub fn render_providers(&mut self, entries: &[ProviderEntry]) -> std::io::Result<()> {
+        for entry in entries {
+            self.stdout.set_color(ColorSpec::new().set_bold(true))?;
+            write!(self.stdout, "{:<18}", entry.name)?;
+            self.stdout.reset()?;
+            write!(self.stdout, "{:<14}", entry.prefix.to_lowercase())?;
+            if !entry.is_configured() {
+                writeln!(self.stdout, "not configured")?;
+                continue;
+            }
+            match entry.account_names() {
+                Some(names) => writeln!(self.stdout, "accounts {}", names.join(", "))?,
+                None => writeln!(self.stdout, "configured")?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the contributions per day, the breakdowns (with the kinds of each week) and the
+    /// status of every provider as a JSON document. With an `error`, the contributions are left out and the error is given
+    /// instead.
+    pub fn render_json(
+        &mut self,
+        contributions_per_row: &[Vec<Option<ContributionDay>>],
+        breakdowns: &[(String, ContributionBreakdown)],
+        reports: &[ProviderReport],
+        error: Option<&str>,
+    ) -> std::io::Result<()> {
+        let mut days: Vec<&ContributionDay> =
+            contributions_per_row.iter().flatten().flatten().collect();
+        days.sort_by(|a, b| a.date.cmp(&b.date));
+        // The first row of the graph holds the first day of each week.
+        let week_starts: Vec<&str> = contributions_per_row
+            .first()
+            .into_iter()
+            .flatten()
+            .map(|day| day.as_ref().map_or("", |day| day.date.as_str()))
+            .collect();
+
+        let providers: Vec<serde_json::Value> = reports
+            .iter()
+            .map(|report| {
+                let (status, error) = match &report.status {
+                    ProviderStatus::Completed => ("completed", None),
+                    ProviderStatus::NotConfigured => ("not_configured", None),
+                    ProviderStatus::TimedOut(timeout) => (
+                        "timed_out",
+                        Some(format!("timed out after {}s", timeout.as_secs())),
+                    ),
+                    ProviderStatus::Failed(error) => ("failed", Some(error.to_string())),
+                };
+                let breakdown = breakdowns
+                    .iter()
+                    .filter(|_| error.is_none())
+                    .find(|(provider, _)| *provider == report.provider)
+                    .map(|(_, breakdown)| {
+                        json!({
+                            "commits": breakdown.kinds.commits,
+                            "pull_requests": breakdown.kinds.pull_requests,
+                            "issues": breakdown.kinds.issues,
+                            "reviews": breakdown.kinds.reviews,
+                            "comments": breakdown.kinds.comments,
+                            "private": breakdown.restricted,
+                            "repositories": breakdown
+                                .repositories
+                                .iter()
+                                .map(|(name, commits)| json!({ "name": name, "commits": commits }))
+                                .collect::<Vec<_>>(),
+                            "weeks": breakdown
+                                .weeks
+                                .iter()
+                                .zip(&week_starts)
+                                .map(|(counts, start)| {
+                                    json!({
+                                        "start": start,
+                                        "commits": counts.commits,
+                                        "pull_requests": counts.pull_requests,
+                                        "issues": counts.issues,
+                                        "reviews": counts.reviews,
+                                        "comments": counts.comments,
+                                    })
+                                })
+                                .collect::<Vec<_>>(),
+                        })
+                    });
+                json!({
+                    "provider": report.provider,
+                    "status": status,
+                    "error": error,
+                    "breakdown": breakdown,
+                })
+            })
+            .collect();
+
+        let (total, days) = match error {
+            Some(_) => (None, None),
+            None => (
+                Some(days.iter().map(|day| day.contribution_count).sum::<i64>()),
+                Some(
+                    days.iter()
+                        .map(|day| json!({ "date": day.date, "count": day.contribution_count }))
+                        .collect::<Vec<_>>(),
+                ),
+            ),
+        };
+        let document = json!({
+            "total": total,
+            "days": days,
+            "providers": providers,
+            "error": error,
+        });
+        writeln!(self.stdout, "{:#}", document)
+    }
 }
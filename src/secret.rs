@@ -0,0 +1,176 @@
+//! Tokens and passwords, and the places other than the settings themselves they can be read
+//! from.
+
+use crate::errors::ProviderError;
+use std::fmt;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::sync::Mutex;
+
+/// Held while a secret command runs. Accounts are loaded concurrently, and commands that
+/// prompt, such as `pass` asking for a passphrase, would otherwise read from the terminal at
+/// the same time.
+static TERMINAL: Mutex<()> = Mutex::const_new(());
+
+/// A token or password, which is left out of debug output.
+#[derive(Clone)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(secret: String) -> Self {
+        Self(secret)
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(REDACTED)")
+    }
+}
+
+/// Where a secret can be read from, other than the setting itself.
+#[derive(Debug, Clone, Copy)]
+pub enum Source {
+    File,
+    Command,
+    GitCredential,
+    Keyring,
+}
+
+impl Source {
+    /// Reads the secret from where `value` points. `setting` names where `value` was set.
+    /// Commands are killed when the returned future is dropped, such as when a provider times
+    /// out.
+    pub async fn read(self, value: &str, setting: &str) -> Result<Secret, ProviderError> {
+        match self {
+            Self::File => from_file(value, setting).await,
+            Self::Command => from_command(value, setting).await,
+            Self::GitCredential => from_git_credential(value, setting).await,
+            Self::Keyring => from_keyring(value, setting).await,
+        }
+    }
+}
+
+/// Takes the secret from the first line, as `pass show` prints other fields after it and
+/// editors end files with a newline.
+fn first_line(secret: &str, source: &str) -> Result<Secret, ProviderError> {
+    let secret = secret.lines().next().unwrap_or_default().trim();
+    if secret.is_empty() {
+        return Err(ProviderError::ConfigError(format!(
+            "{} did not yield a secret",
+            source
+        )));
+    }
+    Ok(Secret::new(secret.to_string()))
+}
+
+/// Reads the secret in the file at `path`. `setting` names where the path was set.
+async fn from_file(path: &str, setting: &str) -> Result<Secret, ProviderError> {
+    let contents = tokio::fs::read_to_string(path).await.map_err(|e| {
+        ProviderError::ConfigError(format!("Failed to read {} ({}): {}", path, setting, e))
+    })?;
+    first_line(&contents, setting)
+}
+
+/// Runs `command` through the shell and reads the secret it prints, such as that of
+/// `pass show github`. Prompts of the command, such as for a passphrase, are passed through,
+/// one command at a time.
+async fn from_command(command: &str, setting: &str) -> Result<Secret, ProviderError> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    let _terminal = TERMINAL.lock().await;
+    let output = shell
+        .arg(command)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| ProviderError::CommandError(format!("Failed to run {}: {}", setting, e)))?;
+    if !output.status.success() {
+        return Err(ProviderError::CommandError(format!(
+            "{} exited with {}",
+            setting, output.status
+        )));
+    }
+    first_line(&String::from_utf8_lossy(&output.stdout), setting)
+}
+
+/// Asks git's credential helpers for the password of `url`, as `git credential fill` does.
+async fn from_git_credential(url: &str, setting: &str) -> Result<Secret, ProviderError> {
+    let mut child = Command::new("git")
+        .args(["credential", "fill"])
+        // Fail rather than prompt when no helper knows the credential.
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| ProviderError::CommandError(format!("Failed to run git: {}", e)))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(format!("url={}\n\n", url).as_bytes())
+            .await?;
+    }
+    let output = child.wait_with_output().await?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let password = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("password="))
+        .filter(|_| output.status.success());
+    match password {
+        Some(password) => first_line(password, setting),
+        None => Err(ProviderError::ConfigError(format!(
+            "git has no credential for {} ({})",
+            url, setting
+        ))),
+    }
+}
+
+/// Looks up a secret in the Secret Service keyring (GNOME Keyring, KWallet) through
+/// `secret-tool`, by attribute and value pairs such as `service github user me`.
+async fn from_keyring(attributes: &str, setting: &str) -> Result<Secret, ProviderError> {
+    let attributes: Vec<&str> = attributes.split_whitespace().collect();
+    if attributes.is_empty() || !attributes.len().is_multiple_of(2) {
+        return Err(ProviderError::ConfigError(format!(
+            "{} must be pairs of attributes and values, such as `service github user me`",
+            setting
+        )));
+    }
+
+    let output = Command::new("secret-tool")
+        .arg("lookup")
+        .args(&attributes)
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| {
+            ProviderError::CommandError(format!(
+                "Failed to run secret-tool, which comes with libsecret: {}",
+                e
+            ))
+        })?;
+    if !output.status.success() {
+        return Err(ProviderError::ConfigError(format!(
+            "No secret in the keyring matches {} ({})",
+            attributes.join(" "),
+            setting
+        )));
+    }
+    first_line(&String::from_utf8_lossy(&output.stdout), setting)
+}
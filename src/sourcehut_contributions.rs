@@ -0,0 +1,332 @@
+use crate::{
+    ProviderError,
+    http::{HttpClient, Quota},
+    processor::Event,
+    provider::{BoxFuture, Events, Provider, into_events, required_secret, required_var, var},
+    secret::Secret,
+};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+#[derive(Debug)]
+struct SourceHutConfig {
+    server: String,
+    username: String,
+    token: Secret,
+    emails: Vec<String>,
+}
+
+impl SourceHutConfig {
+    async fn from_env(prefix: &str) -> Result<Self, ProviderError> {
+        Ok(Self {
+            server: var(prefix, "SERVER")
+                .unwrap_or_else(|| "https://git.sr.ht".to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            username: required_var(prefix, "USERNAME")?
+                .trim_start_matches('~')
+                .to_string(),
+            token: required_secret(prefix, "TOKEN").await?,
+            emails: var(prefix, "EMAILS")
+                .map(|emails| {
+                    emails
+                        .split(',')
+                        .map(|email| email.trim().to_lowercase())
+                        .filter(|email| !email.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+    }
+}
+
+const SOURCEHUT_REPOSITORIES_QUERY: &str = r#"
+query($username: String!, $cursor: Cursor) {
+    user(username: $username) {
+        repositories(cursor: $cursor) {
+            results {
+                name
+                updated
+            }
+            cursor
+        }
+    }
+}
+"#;
+
+const SOURCEHUT_ME_QUERY: &str = r#"
+query {
+    me {
+        email
+    }
+}
+"#;
+
+const SOURCEHUT_LOG_QUERY: &str = r#"
+query($username: String!, $repository: String!, $cursor: Cursor) {
+    user(username: $username) {
+        repository(name: $repository) {
+            log(cursor: $cursor) {
+                results {
+                    id
+                    author {
+                        email
+                        time
+                    }
+                }
+                cursor
+            }
+        }
+    }
+}
+"#;
+
+#[derive(Deserialize, Debug)]
+struct SourceHutResponse<T> {
+    data: Option<T>,
+    errors: Option<Vec<SourceHutError>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct SourceHutError {
+    message: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct SourceHutCursor<T> {
+    results: Vec<T>,
+    cursor: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct SourceHutRepositoriesData {
+    user: Option<SourceHutRepositoriesUser>,
+}
+
+#[derive(Deserialize, Debug)]
+struct SourceHutRepositoriesUser {
+    repositories: SourceHutCursor<SourceHutRepository>,
+}
+
+#[derive(Deserialize, Debug)]
+struct SourceHutRepository {
+    name: String,
+    updated: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct SourceHutMeData {
+    me: SourceHutMe,
+}
+
+#[derive(Deserialize, Debug)]
+struct SourceHutMe {
+    email: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct SourceHutLogData {
+    user: Option<SourceHutLogUser>,
+}
+
+#[derive(Deserialize, Debug)]
+struct SourceHutLogUser {
+    repository: Option<SourceHutLogRepository>,
+}
+
+#[derive(Deserialize, Debug)]
+struct SourceHutLogRepository {
+    log: SourceHutCursor<SourceHutCommit>,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+struct SourceHutCommit {
+    id: String,
+    author: SourceHutSignature,
+}
+
+#[derive(Deserialize, Debug)]
+struct SourceHutSignature {
+    email: String,
+    time: String,
+}
+
+impl Event for SourceHutCommit {
+    fn timestamp(&self) -> Result<OffsetDateTime, ProviderError> {
+        OffsetDateTime::parse(&self.author.time, &Rfc3339)
+            .map_err(|e| ProviderError::DateError(format!("Failed to parse date: {}", e)))
+    }
+    fn contributions(&self) -> i64 {
+        1
+    }
+}
+
+pub struct SourceHutClient {
+    client: HttpClient,
+    config: SourceHutConfig,
+}
+
+impl SourceHutClient {
+    fn new(config: SourceHutConfig) -> Self {
+        Self {
+            client: HttpClient::new("SourceHut"),
+            config,
+        }
+    }
+
+    async fn query<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<T, ProviderError> {
+        let body = serde_json::json!({
+            "query": query,
+            "variables": variables,
+        });
+
+        let request = self
+            .client
+            .post(format!("{}/query", self.config.server))
+            .bearer_auth(self.config.token.expose())
+            .json(&body);
+        let response = self.client.send(request).await?;
+        let response = self.client.error_for_status(response).await?;
+
+        let response: SourceHutResponse<T> = self.client.json(response).await?;
+        if let Some(errors) = response.errors.filter(|errors| !errors.is_empty()) {
+            let messages: Vec<String> = errors.into_iter().map(|error| error.message).collect();
+            return Err(ProviderError::ApiError(messages.join("; ")));
+        }
+        response
+            .data
+            .ok_or_else(|| ProviderError::ApiError("Response contained no data".into()))
+    }
+
+    async fn fetch_repositories(&self) -> Result<Vec<SourceHutRepository>, ProviderError> {
+        let mut repositories = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let data: SourceHutRepositoriesData = self
+                .query(
+                    SOURCEHUT_REPOSITORIES_QUERY,
+                    serde_json::json!({
+                        "username": self.config.username,
+                        "cursor": cursor,
+                    }),
+                )
+                .await?;
+            let page = data
+                .user
+                .ok_or_else(|| {
+                    ProviderError::ApiError(format!("User {} not found", self.config.username))
+                })?
+                .repositories;
+
+            repositories.extend(page.results);
+            match page.cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        Ok(repositories)
+    }
+
+    /// Author emails of the user's own commits, from `<PREFIX>_EMAILS` or else the email of the
+    /// account the token belongs to.
+    async fn fetch_emails(&self) -> Result<Vec<String>, ProviderError> {
+        if !self.config.emails.is_empty() {
+            return Ok(self.config.emails.clone());
+        }
+        let data: SourceHutMeData = self
+            .query(SOURCEHUT_ME_QUERY, serde_json::json!({}))
+            .await?;
+        Ok(vec![data.me.email.to_lowercase()])
+    }
+
+    async fn fetch_commits(
+        &self,
+        repository: &SourceHutRepository,
+        emails: &[String],
+        start_date: OffsetDateTime,
+    ) -> Result<Vec<SourceHutCommit>, ProviderError> {
+        let mut commits = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let data: SourceHutLogData = self
+                .query(
+                    SOURCEHUT_LOG_QUERY,
+                    serde_json::json!({
+                        "username": self.config.username,
+                        "repository": repository.name,
+                        "cursor": cursor,
+                    }),
+                )
+                .await?;
+            // Empty repositories have no log to walk.
+            let Some(log) = data
+                .user
+                .and_then(|user| user.repository)
+                .map(|repository| repository.log)
+            else {
+                break;
+            };
+
+            // The log is walked from the tip backwards, stop once the window is left.
+            let mut reached_start = false;
+            for commit in log.results {
+                if commit.timestamp()? < start_date {
+                    reached_start = true;
+                    continue;
+                }
+                if emails.contains(&commit.author.email.to_lowercase()) {
+                    commits.push(commit);
+                }
+            }
+
+            match log.cursor {
+                Some(next) if !reached_start => cursor = Some(next),
+                _ => break,
+            }
+        }
+        Ok(commits)
+    }
+}
+
+impl Provider for SourceHutClient {
+    fn name(&self) -> &str {
+        "SourceHut"
+    }
+
+    async fn from_env(prefix: &str) -> Result<Self, ProviderError> {
+        Ok(Self::new(SourceHutConfig::from_env(prefix).await?))
+    }
+
+    fn fetch_events(
+        &self,
+        start_date: OffsetDateTime,
+        _end_date: OffsetDateTime,
+    ) -> BoxFuture<'_, Result<Events, ProviderError>> {
+        Box::pin(async move {
+            let emails = self.fetch_emails().await?;
+            let mut events = Vec::new();
+            for repository in self.fetch_repositories().await? {
+                let updated =
+                    OffsetDateTime::parse(&repository.updated, &Rfc3339).map_err(|e| {
+                        ProviderError::DateError(format!("Failed to parse date: {}", e))
+                    })?;
+                if updated < start_date {
+                    continue;
+                }
+                events.extend(self.fetch_commits(&repository, &emails, start_date).await?);
+            }
+            Ok(into_events(events))
+        })
+    }
+
+    fn quota(&self) -> Option<Quota> {
+        self.client.quota()
+    }
+}
@@ -4,6 +4,91 @@ pub struct ContributionCollection {
     pub provider: String,
     pub contributions: Vec<(i64, Vec<ContributionDay>)>,
     pub max_contributions: i64,
+    pub breakdown: Option<ContributionBreakdown>,
+}
+
+/// Number of contributions of each kind.
+#[derive(Debug, Clone, Default)]
+pub struct KindCounts {
+    pub commits: i64,
+    pub pull_requests: i64,
+    pub issues: i64,
+    pub reviews: i64,
+    pub comments: i64,
+}
+
+impl KindCounts {
+    pub fn get(&self, kind: ContributionKind) -> i64 {
+        match kind {
+            ContributionKind::Commit => self.commits,
+            ContributionKind::PullRequest => self.pull_requests,
+            ContributionKind::Issue => self.issues,
+            ContributionKind::Review => self.reviews,
+            ContributionKind::Comment => self.comments,
+        }
+    }
+
+    pub fn add(&mut self, kind: ContributionKind, contributions: i64) {
+        match kind {
+            ContributionKind::Commit => self.commits += contributions,
+            ContributionKind::PullRequest => self.pull_requests += contributions,
+            ContributionKind::Issue => self.issues += contributions,
+            ContributionKind::Review => self.reviews += contributions,
+            ContributionKind::Comment => self.comments += contributions,
+        }
+    }
+
+    pub fn merge(&mut self, other: &KindCounts) {
+        self.commits += other.commits;
+        self.pull_requests += other.pull_requests;
+        self.issues += other.issues;
+        self.reviews += other.reviews;
+        self.comments += other.comments;
+    }
+
+    /// The kind most contributions were, if there were any. Ties go to the kind listed first.
+    pub fn most(&self) -> Option<ContributionKind> {
+        ContributionKind::ALL
+            .into_iter()
+            .filter(|&kind| self.get(kind) > 0)
+            .rev()
+            .max_by_key(|&kind| self.get(kind))
+    }
+}
+
+/// What a provider's contributions consist of, for providers that report it.
+#[derive(Debug, Clone, Default)]
+pub struct ContributionBreakdown {
+    /// The contributions of the whole range by kind.
+    pub kinds: KindCounts,
+    /// Contributions to private repositories that cannot be shown in detail.
+    pub restricted: i64,
+    /// Commit contributions per repository, most active first.
+    pub repositories: Vec<(String, i64)>,
+    /// The contributions of each week of the graph by kind, from its first week on.
+    pub weeks: Vec<KindCounts>,
+}
+
+impl ContributionBreakdown {
+    /// Counts contributions of the given kind in the given week of the graph.
+    pub fn add(&mut self, week: usize, kind: ContributionKind, contributions: i64) {
+        self.kinds.add(kind, contributions);
+        if self.weeks.len() <= week {
+            self.weeks.resize_with(week + 1, KindCounts::default);
+        }
+        self.weeks[week].add(kind, contributions);
+    }
+
+    pub fn add_repository_commits(&mut self, repository: String, commits: i64) {
+        match self
+            .repositories
+            .iter_mut()
+            .find(|(name, _)| *name == repository)
+        {
+            Some((_, count)) => *count += commits,
+            None => self.repositories.push((repository, commits)),
+        }
+    }
 }
 
 #[allow(dead_code)]
@@ -14,3 +99,80 @@ pub struct ContributionDay {
     pub weeknumber: i64,
     pub weekday: i64,
 }
+
+/// What a single contribution event was.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContributionKind {
+    Commit,
+    PullRequest,
+    Issue,
+    Review,
+    Comment,
+}
+
+impl ContributionKind {
+    pub const ALL: [ContributionKind; 5] = [
+        Self::Commit,
+        Self::PullRequest,
+        Self::Issue,
+        Self::Review,
+        Self::Comment,
+    ];
+
+    /// What contributions of this kind are called, such as `pull requests`.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Commit => "commits",
+            Self::PullRequest => "pull requests",
+            Self::Issue => "issues",
+            Self::Review => "reviews",
+            Self::Comment => "comments",
+        }
+    }
+
+    /// The letter weeks that mostly consist of this kind are marked with below the graph.
+    pub fn symbol(self) -> char {
+        match self {
+            Self::Commit => 'C',
+            Self::PullRequest => 'P',
+            Self::Issue => 'I',
+            Self::Review => 'R',
+            Self::Comment => 'M',
+        }
+    }
+}
+
+/// How fetching a provider's contributions went.
+#[derive(Debug)]
+pub enum ProviderStatus {
+    Completed,
+    TimedOut(std::time::Duration),
+    Failed(crate::errors::ProviderError),
+    /// None of the provider's variables are set.
+    NotConfigured,
+}
+
+impl ProviderStatus {
+    /// Whether the provider is missing from the graph although it is configured.
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Self::TimedOut(_) | Self::Failed(_))
+    }
+}
+
+#[derive(Debug)]
+pub struct ProviderReport {
+    pub provider: String,
+    pub status: ProviderStatus,
+    pub quota: Option<crate::http::Quota>,
+}
+
+impl ProviderReport {
+    /// A report of a provider without a rate limit quota.
+    pub fn new(provider: String, status: ProviderStatus) -> Self {
+        Self {
+            provider,
+            status,
+            quota: None,
+        }
+    }
+}